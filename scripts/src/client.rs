@@ -1,48 +1,477 @@
-use reqwest::header::{HeaderMap, HeaderValue, USER_AGENT};
-use std::io::Write;
-use std::sync::Arc;
+use futures::stream::{self, Stream, StreamExt, TryStreamExt};
+use reqwest::StatusCode;
+use reqwest::header::{
+    CONTENT_ENCODING, CONTENT_TYPE, ETAG, HeaderMap, HeaderValue, IF_MODIFIED_SINCE, IF_NONE_MATCH,
+    LAST_MODIFIED, RETRY_AFTER, USER_AGENT,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
 
-use crate::database::Database;
+use crate::database::{BULK_CACHE_KEY, Database};
 use crate::error::{QueryValidationError, ScryfallError};
-use crate::models::{Card, ScryfallSearchResponse};
-use crate::rate_limiter::RateLimiter;
+use crate::models::{Card, CardCollection, CardIdentifier, ScryfallSearchResponse};
+use crate::progress::Progress;
+use crate::rate_limiter::{RateLimiter, RateLimiterStats};
 use crate::validator::QueryValidator;
 
+/// Scryfall API host used unless overridden with [`ScryfallClient::with_base_url`]
+pub const DEFAULT_BASE_URL: &str = "https://api.scryfall.com";
+
+/// Maximum identifiers Scryfall accepts in one `/cards/collection` request
+const COLLECTION_BATCH_SIZE: usize = 75;
+
+/// Pruning is skipped when the bulk snapshot holds fewer ids than this share of the stored cards
+const PRUNE_MIN_RATIO: f64 = 0.9;
+
+/// HTTP cache validators (`ETag` / `Last-Modified`) remembered from a previous response
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheValidators {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+impl CacheValidators {
+    fn from_headers(headers: &HeaderMap) -> Self {
+        let get = |name| {
+            headers
+                .get(name)
+                .and_then(|v: &HeaderValue| v.to_str().ok())
+                .map(|s| s.to_string())
+        };
+        Self {
+            etag: get(ETAG),
+            last_modified: get(LAST_MODIFIED),
+        }
+    }
+
+    /// Conditional request headers (`If-None-Match` / `If-Modified-Since`) for these validators
+    fn conditional_headers(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        if let Some(value) = self
+            .etag
+            .as_deref()
+            .and_then(|v| HeaderValue::from_str(v).ok())
+        {
+            headers.insert(IF_NONE_MATCH, value);
+        }
+        if let Some(value) = self
+            .last_modified
+            .as_deref()
+            .and_then(|v| HeaderValue::from_str(v).ok())
+        {
+            headers.insert(IF_MODIFIED_SINCE, value);
+        }
+        headers
+    }
+}
+
+/// Cache state of the last successful bulk import, used to skip unchanged downloads.
+/// Each import saves it in the `db_metadata` table of the database it filled, so it carries
+/// over to the next run against that database.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BulkCacheState {
+    pub catalog: CacheValidators,
+    pub download_uri: Option<String>,
+    pub download: CacheValidators,
+}
+
+/// Outcome of a bulk import
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BulkImport {
+    /// New bulk data was downloaded and this many cards were stored
+    Stored(usize),
+    /// Scryfall reported the bulk data unchanged since the last import; nothing was downloaded
+    UpToDate,
+    /// The client's cancellation token fired; this many cards were stored before stopping
+    Cancelled(usize),
+}
+
+/// Options for [`ScryfallClient::download_and_store_bulk_with`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BulkImportOptions {
+    /// After a complete import, delete cards that are no longer in the bulk snapshot
+    /// (removed or merged printings) so the table exactly mirrors Scryfall. Skipped when the
+    /// snapshot is much smaller than the table, which points at a truncated download.
+    pub prune_missing: bool,
+    /// Cards stored per upsert transaction, or per `COPY` into an empty table (default 500)
+    pub batch_size: usize,
+    /// Batch transactions run at the same time (default 1, i.e. sequential). Each one holds a
+    /// pool connection, so values above the pool size only queue.
+    pub concurrency: usize,
+}
+
+impl Default for BulkImportOptions {
+    fn default() -> Self {
+        Self {
+            prune_missing: false,
+            batch_size: 500,
+            concurrency: 1,
+        }
+    }
+}
+
+/// Optional caps that stop a paginated fetch early, protecting the rate budget and memory
+/// against overly broad queries. The default has no caps.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FetchLimits {
+    /// Stop after this many result pages
+    pub max_pages: Option<u32>,
+    /// Stop once this many cards have been collected
+    pub max_cards: Option<usize>,
+}
+
+impl FetchLimits {
+    /// No caps: fetch every page
+    pub fn none() -> Self {
+        Self::default()
+    }
+
+    pub fn max_pages(max_pages: u32) -> Self {
+        Self {
+            max_pages: Some(max_pages),
+            ..Self::default()
+        }
+    }
+
+    pub fn max_cards(max_cards: usize) -> Self {
+        Self {
+            max_cards: Some(max_cards),
+            ..Self::default()
+        }
+    }
+
+    fn reached(&self, pages: u32, cards: usize) -> bool {
+        self.max_pages.is_some_and(|max| pages >= max)
+            || self.max_cards.is_some_and(|max| cards >= max)
+    }
+}
+
+/// Result of a paginated fetch
+#[derive(Debug, Clone)]
+pub struct FetchOutcome<T> {
+    pub data: T,
+    /// More results were available but were not fetched, because a [`FetchLimits`] cap was
+    /// hit or the client was cancelled
+    pub truncated: bool,
+    /// Scryfall's `warnings` for the query, e.g. a malformed clause it ignored; each warning
+    /// appears once even if several pages repeat it
+    pub warnings: Vec<String>,
+}
+
+/// What [`ScryfallClient::fetch_and_store`] stored
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StoreSummary {
+    /// Cards written to the database
+    pub stored: usize,
+    /// Cards matching the query according to Scryfall's first page, including any left
+    /// unfetched by limits or cancellation
+    pub total_cards: u64,
+    /// Result pages fetched
+    pub pages: u32,
+}
+
+/// One page of search results from [`ScryfallClient::search_page`]
+#[derive(Debug, Clone)]
+pub struct SearchPage {
+    pub cards: Vec<Card>,
+    /// Cards matching the query across all pages
+    pub total_cards: u32,
+    pub has_more: bool,
+    /// URL to pass back to `search_page` for the next page; `None` on the last page
+    pub next_page: Option<String>,
+    /// Scryfall's non-fatal `warnings` for the query
+    pub warnings: Vec<String>,
+}
+
+/// Dry-run result for one query from [`ScryfallClient::plan_queries`]
+#[derive(Debug, Clone)]
+pub struct QueryPlan {
+    pub query: String,
+    pub valid: bool,
+    /// Why the query was rejected; `None` when it is valid
+    pub error: Option<QueryValidationError>,
+    /// First search page the query would request; `None` when it is invalid
+    pub encoded_url: Option<String>,
+}
+
+/// Cards from several queries merged into one list, plus the queries that failed
+#[derive(Debug, Clone, Default)]
+pub struct DedupedCards {
+    /// Unique cards (by Scryfall `id`) in first-seen order
+    pub cards: Vec<Card>,
+    /// Queries that failed validation or fetching, with their error
+    pub failures: Vec<(String, ScryfallError)>,
+}
+
 /// Optimized client with connection pooling, rate limiting, and query validation
 pub struct ScryfallClient {
     client: reqwest::Client,
     rate_limiter: Arc<RateLimiter>,
     headers: HeaderMap,
     validator: QueryValidator,
+    bulk_cache: Mutex<BulkCacheState>,
+    base_url: String,
+    cancel: CancellationToken,
+    max_retries: u32,
 }
 
-impl ScryfallClient {
-    pub fn new() -> Self {
+/// Configures a [`ScryfallClient`]; start from [`ScryfallClient::builder`]. Unset options keep
+/// the values [`ScryfallClient::new`] uses.
+#[derive(Debug, Clone)]
+pub struct ScryfallClientBuilder {
+    user_agent: String,
+    timeout: Duration,
+    connect_timeout: Duration,
+    rate_limit_delay: Duration,
+    max_concurrent: usize,
+    max_retries: u32,
+    base_url: String,
+}
+
+impl Default for ScryfallClientBuilder {
+    fn default() -> Self {
+        Self {
+            user_agent: "MTGBuilderApp/1.0".to_string(),
+            timeout: Duration::from_secs(30),
+            connect_timeout: Duration::from_secs(10),
+            // Scryfall allows ~10 req/sec, we use 100ms delay to be safe
+            rate_limit_delay: Duration::from_millis(100),
+            max_concurrent: 5,
+            max_retries: 0,
+            base_url: DEFAULT_BASE_URL.to_string(),
+        }
+    }
+}
+
+impl ScryfallClientBuilder {
+    /// `User-Agent` sent with every request; Scryfall asks for one that identifies the app
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = user_agent.into();
+        self
+    }
+
+    /// Total time allowed for an API request (the bulk download has its own, longer timeout)
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Time allowed to establish a connection
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = timeout;
+        self
+    }
+
+    /// Minimum delay between API requests
+    pub fn rate_limit_delay(mut self, delay: Duration) -> Self {
+        self.rate_limit_delay = delay;
+        self
+    }
+
+    /// Maximum number of requests waiting on the rate limiter at once; also the idle
+    /// connection pool size
+    pub fn max_concurrent(mut self, max_concurrent: usize) -> Self {
+        self.max_concurrent = max_concurrent;
+        self
+    }
+
+    /// Times a request is retried after a 429, a 5xx or a connection failure (default 0)
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// API host to send requests to, e.g. a mock server in tests
+    pub fn base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    pub fn build(self) -> Result<ScryfallClient, ScryfallError> {
+        if self.max_concurrent == 0 {
+            return Err(ScryfallError::InvalidConfig(
+                "max_concurrent must be at least 1".to_string(),
+            ));
+        }
+
         let mut headers = HeaderMap::new();
-        headers.insert(USER_AGENT, HeaderValue::from_static("MTGBuilderApp/1.0"));
+        let user_agent = HeaderValue::from_str(&self.user_agent).map_err(|_| {
+            ScryfallError::InvalidConfig(format!("invalid user agent '{}'", self.user_agent))
+        })?;
+        headers.insert(USER_AGENT, user_agent);
 
         // Optimized client with connection pooling and keepalive
         let client = reqwest::Client::builder()
-            .pool_max_idle_per_host(5)
+            .pool_max_idle_per_host(self.max_concurrent)
             .pool_idle_timeout(Duration::from_secs(30))
             .tcp_keepalive(Duration::from_secs(60))
             .tcp_nodelay(true)
-            .timeout(Duration::from_secs(30))
-            .connect_timeout(Duration::from_secs(10))
+            .timeout(self.timeout)
+            .connect_timeout(self.connect_timeout)
             .build()
-            .expect("Failed to build HTTP client");
+            .map_err(|e| ScryfallError::RequestError(Arc::new(e)))?;
 
-        // Scryfall allows ~10 req/sec, we use 100ms delay to be safe
-        let rate_limiter = Arc::new(RateLimiter::new(5, 100));
+        let rate_limiter = Arc::new(RateLimiter::new(
+            self.max_concurrent,
+            self.rate_limit_delay.as_millis() as u64,
+        ));
         let validator = QueryValidator::new();
 
-        Self {
+        Ok(ScryfallClient {
             client,
             rate_limiter,
             headers,
             validator,
+            bulk_cache: Mutex::new(BulkCacheState::default()),
+            base_url: self.base_url.trim_end_matches('/').to_string(),
+            cancel: CancellationToken::new(),
+            max_retries: self.max_retries,
+        })
+    }
+}
+
+impl ScryfallClient {
+    pub fn new() -> Self {
+        Self::builder()
+            .build()
+            .expect("Failed to build HTTP client")
+    }
+
+    /// Client configured from the environment, for deployments that cannot recompile:
+    /// `SCRYFALL_USER_AGENT`, `SCRYFALL_RATE_LIMIT_MS` and `SCRYFALL_MAX_CONCURRENT`. Unset or
+    /// empty variables keep the [`ScryfallClient::new`] defaults; unparsable ones are an
+    /// `InvalidConfig` error.
+    pub fn from_env() -> Result<Self, ScryfallError> {
+        Self::from_lookup(|name| std::env::var(name).ok())
+    }
+
+    /// Same as [`from_env`](Self::from_env), but reads the variables through `lookup`, e.g.
+    /// from a config file or a test's map, instead of the process environment
+    pub fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Result<Self, ScryfallError> {
+        let var = |name| lookup(name).filter(|value: &String| !value.is_empty());
+
+        let mut builder = Self::builder();
+        if let Some(user_agent) = var("SCRYFALL_USER_AGENT") {
+            builder = builder.user_agent(user_agent);
+        }
+        if let Some(delay_ms) = var("SCRYFALL_RATE_LIMIT_MS") {
+            builder = builder.rate_limit_delay(Duration::from_millis(parse_env_var(
+                "SCRYFALL_RATE_LIMIT_MS",
+                &delay_ms,
+            )?));
+        }
+        if let Some(max_concurrent) = var("SCRYFALL_MAX_CONCURRENT") {
+            builder =
+                builder.max_concurrent(parse_env_var("SCRYFALL_MAX_CONCURRENT", &max_concurrent)?);
         }
+        builder.build()
+    }
+
+    /// Start configuring a client; see [`ScryfallClientBuilder`]
+    pub fn builder() -> ScryfallClientBuilder {
+        ScryfallClientBuilder::default()
+    }
+
+    /// Validate queries with `validator`, e.g. one built with
+    /// [`QueryValidator::with_set_codes`] to catch mistyped set codes
+    pub fn with_validator(mut self, validator: QueryValidator) -> Self {
+        self.validator = validator;
+        self
+    }
+
+    /// Send requests through `limiter` instead of this client's own. Scryfall's rate limit is
+    /// per caller, not per client, so when several clients (e.g. one per worker) run at once
+    /// they should all share a single limiter to stay within the global budget.
+    pub fn with_rate_limiter(mut self, limiter: Arc<RateLimiter>) -> Self {
+        self.rate_limiter = limiter;
+        self
+    }
+
+    /// Counters from the rate limiter this client sends its requests through
+    pub fn rate_limiter_stats(&self) -> RateLimiterStats {
+        self.rate_limiter.stats()
+    }
+
+    /// Stop long-running operations when `token` is cancelled.
+    /// `fetch_all_json`, `fetch_all_cards`, `fetch_and_store` and `download_and_store_bulk`
+    /// check the token between pages/batches and return what they have collected or stored
+    /// so far instead of failing.
+    pub fn with_cancellation_token(mut self, token: CancellationToken) -> Self {
+        self.cancel = token;
+        self
+    }
+
+    /// Whether this client's cancellation token has been cancelled
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.is_cancelled()
+    }
+
+    /// Point the client at a different API host, e.g. a local mock server or a mirror.
+    /// Pagination follows the `next_page` URLs returned by that host.
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// URL of the first `/cards/search` page for an already-encoded query
+    fn search_url(&self, encoded_query: &str) -> String {
+        format!("{}/cards/search?q={}", self.base_url, encoded_query)
+    }
+
+    /// The API host this client sends requests to
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Cache validators of the last bulk import this client ran or loaded, mirroring what it
+    /// read from or wrote to `db_metadata`
+    pub fn bulk_cache_state(&self) -> BulkCacheState {
+        self.bulk_cache.lock().unwrap().clone()
+    }
+
+    fn set_bulk_cache_state(&self, state: BulkCacheState) {
+        *self.bulk_cache.lock().unwrap() = state;
+    }
+
+    /// Cache validators for the next bulk import into `db`, read from its `db_metadata`. An
+    /// empty `cards` table gets none, so a wiped or brand-new database is always filled.
+    async fn load_bulk_cache_state(&self, db: &Database) -> Result<BulkCacheState, ScryfallError> {
+        let card_count = db
+            .get_card_count()
+            .await
+            .map_err(|e| ScryfallError::DatabaseError(e.to_string()))?;
+        let cache = if card_count == 0 {
+            BulkCacheState::default()
+        } else {
+            let stored = db
+                .get_metadata(BULK_CACHE_KEY)
+                .await
+                .map_err(|e| ScryfallError::DatabaseError(e.to_string()))?;
+            // An unreadable entry only costs one full download, so fall back to no validators
+            stored
+                .and_then(|json| serde_json::from_str(&json).ok())
+                .unwrap_or_default()
+        };
+        self.set_bulk_cache_state(cache.clone());
+        Ok(cache)
+    }
+
+    /// Remember cache validators in memory and in `db_metadata`
+    async fn save_bulk_cache_state(
+        &self,
+        db: &Database,
+        state: BulkCacheState,
+    ) -> Result<(), ScryfallError> {
+        let json = serde_json::to_string(&state)
+            .map_err(|e| ScryfallError::DatabaseError(e.to_string()))?;
+        db.set_metadata(BULK_CACHE_KEY, &json)
+            .await
+            .map_err(|e| ScryfallError::DatabaseError(e.to_string()))?;
+        self.set_bulk_cache_state(state);
+        Ok(())
     }
 
     /// Validate a query without sending it
@@ -50,45 +479,235 @@ impl ScryfallClient {
         self.validator.validate(query)
     }
 
-    async fn fetch_page(&self, url: &str) -> Result<ScryfallSearchResponse, reqwest::Error> {
-        self.rate_limiter.acquire().await;
+    /// Sends a rate-limited request built by `request`, retrying up to `max_retries` times on
+    /// 429, 5xx and connection errors. Waits for `Retry-After` when Scryfall sends it,
+    /// otherwise backs off exponentially from 500ms. The wait is a penalty on the rate
+    /// limiter, so every client sharing it backs off too.
+    async fn send(
+        &self,
+        request: impl Fn() -> reqwest::RequestBuilder,
+    ) -> Result<reqwest::Response, ScryfallError> {
+        let mut attempt = 0;
+        loop {
+            self.rate_limiter.acquire().await;
+            let result = request().headers(self.headers.clone()).send().await;
+
+            let retryable = match &result {
+                Ok(response) => {
+                    response.status() == StatusCode::TOO_MANY_REQUESTS
+                        || response.status().is_server_error()
+                }
+                Err(e) => e.is_connect() || e.is_timeout(),
+            };
+            if !retryable || attempt >= self.max_retries {
+                return Ok(result?);
+            }
 
-        let response = self
-            .client
-            .get(url)
-            .headers(self.headers.clone())
-            .send()
-            .await?;
+            let retry_after = result
+                .as_ref()
+                .ok()
+                .and_then(|response| response.headers().get(RETRY_AFTER))
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.trim().parse::<u64>().ok())
+                .map(Duration::from_secs);
+            let backoff = Duration::from_millis(500 * 2u64.pow(attempt.min(6)));
+
+            attempt += 1;
+            self.rate_limiter
+                .penalize(Instant::now() + retry_after.unwrap_or(backoff));
+        }
+    }
 
-        response.error_for_status()?.json().await
+    async fn fetch_page(&self, url: &str) -> Result<ScryfallSearchResponse, ScryfallError> {
+        let response = self.send(|| self.client.get(url)).await?;
+
+        Ok(check_status(response).await?.json().await?)
     }
 
     /// Fetches a single page of JSON response
     async fn fetch_json_page(&self, url: &str) -> Result<serde_json::Value, ScryfallError> {
-        self.rate_limiter.acquire().await;
+        let response = self.send(|| self.client.get(url)).await?;
+
+        let json: serde_json::Value = check_status(response).await?.json().await?;
+        Ok(json)
+    }
+
+    /// Look up a single card by name via `/cards/named`.
+    /// With `fuzzy` set, Scryfall tolerates misspellings and partial names; otherwise the
+    /// name must match exactly (case-insensitive). Returns `ScryfallError::NotFound` when
+    /// no card matches.
+    pub async fn fetch_card_named(
+        &self,
+        name: &str,
+        fuzzy: bool,
+    ) -> Result<serde_json::Value, ScryfallError> {
+        let mode = if fuzzy { "fuzzy" } else { "exact" };
+        let url = format!(
+            "{}/cards/named?{}={}",
+            self.base_url,
+            mode,
+            urlencoding::encode(name)
+        );
+
+        self.fetch_json_page(&url).await
+    }
 
+    /// Fetches a single page of JSON with conditional request headers.
+    /// Returns `None` when the server answers `304 Not Modified`.
+    async fn fetch_json_page_if_modified(
+        &self,
+        url: &str,
+        validators: &CacheValidators,
+    ) -> Result<Option<(serde_json::Value, CacheValidators)>, ScryfallError> {
         let response = self
-            .client
-            .get(url)
-            .headers(self.headers.clone())
-            .send()
+            .send(|| {
+                self.client
+                    .get(url)
+                    .headers(validators.conditional_headers())
+            })
             .await?;
 
-        let json: serde_json::Value = response.error_for_status()?.json().await?;
+        if response.status() == StatusCode::NOT_MODIFIED {
+            return Ok(None);
+        }
+
+        let response = check_status(response).await?;
+        let new_validators = CacheValidators::from_headers(response.headers());
+        let json: serde_json::Value = response.json().await?;
+        Ok(Some((json, new_validators)))
+    }
+
+    /// Card name suggestions for a partial name via `/cards/autocomplete`.
+    /// Blank input returns an empty list without sending a request.
+    pub async fn autocomplete(&self, partial: &str) -> Result<Vec<String>, ScryfallError> {
+        let partial = partial.trim();
+        if partial.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let url = format!(
+            "{}/cards/autocomplete?q={}",
+            self.base_url,
+            urlencoding::encode(partial)
+        );
+        let json = self.fetch_json_page(&url).await?;
+
+        Ok(json["data"]
+            .as_array()
+            .map(|names| {
+                names
+                    .iter()
+                    .filter_map(|n| n.as_str().map(|s| s.to_string()))
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
+    /// A card by Scryfall id, served from `db` when it is already stored. On a miss the card
+    /// is fetched via `/cards/{id}` and upserted, so the next lookup stays local.
+    /// Returns `ScryfallError::NotFound` for an unknown card id.
+    pub async fn fetch_card_by_id(
+        &self,
+        id: &str,
+        db: &Database,
+    ) -> Result<serde_json::Value, ScryfallError> {
+        if let Some(card) = db
+            .get_card_by_id(id)
+            .await
+            .map_err(|e| ScryfallError::DatabaseError(e.to_string()))?
+        {
+            return Ok(card);
+        }
+
+        let url = format!("{}/cards/{}", self.base_url, urlencoding::encode(id));
+        let card = self.fetch_json_page(&url).await?;
+        db.upsert_card(&card)
+            .await
+            .map_err(|e| ScryfallError::DatabaseError(e.to_string()))?;
+
+        Ok(card)
+    }
+
+    /// Rulings for a card via `/cards/{id}/rulings`.
+    /// Returns `ScryfallError::NotFound` for an unknown card id.
+    pub async fn fetch_rulings(
+        &self,
+        card_id: &str,
+    ) -> Result<Vec<serde_json::Value>, ScryfallError> {
+        let url = format!(
+            "{}/cards/{}/rulings",
+            self.base_url,
+            urlencoding::encode(card_id)
+        );
+        let json = self.fetch_json_page(&url).await?;
+
+        Ok(json["data"].as_array().cloned().unwrap_or_default())
+    }
+
+    /// Every set in Scryfall's catalog via `/sets`, following `next_page` if the list is split
+    pub async fn fetch_sets(&self) -> Result<Vec<serde_json::Value>, ScryfallError> {
+        let mut sets = Vec::new();
+        let mut next_url = Some(format!("{}/sets", self.base_url));
+
+        while let Some(url) = next_url {
+            let json = self.fetch_json_page(&url).await?;
+            if let Some(data) = json["data"].as_array() {
+                sets.extend(data.iter().cloned());
+            }
+
+            next_url = if json["has_more"].as_bool().unwrap_or(false) {
+                json["next_page"].as_str().map(|s| s.to_string())
+            } else {
+                None
+            };
+        }
+
+        Ok(sets)
+    }
+
+    /// Sends a rate-limited JSON POST request and returns the JSON response
+    async fn post_json(
+        &self,
+        url: &str,
+        body: &serde_json::Value,
+    ) -> Result<serde_json::Value, ScryfallError> {
+        let response = self.send(|| self.client.post(url).json(body)).await?;
+
+        let json: serde_json::Value = check_status(response).await?.json().await?;
         Ok(json)
     }
 
+    /// Look up many cards at once via `POST /cards/collection`.
+    /// Identifiers are sent in chunks of 75 (Scryfall's per-request limit); found cards and
+    /// unmatched identifiers are aggregated across all chunks.
+    pub async fn fetch_collection(
+        &self,
+        identifiers: &[CardIdentifier],
+    ) -> Result<CardCollection, ScryfallError> {
+        let url = format!("{}/cards/collection", self.base_url);
+        let mut collection = CardCollection::default();
+
+        for chunk in identifiers.chunks(COLLECTION_BATCH_SIZE) {
+            let body = serde_json::json!({ "identifiers": chunk });
+            let json = self.post_json(&url, &body).await?;
+
+            if let Some(cards) = json["data"].as_array() {
+                collection.data.extend(cards.iter().cloned());
+            }
+            if let Some(missing) = json["not_found"].as_array() {
+                collection.not_found.extend(missing.iter().cloned());
+            }
+        }
+
+        Ok(collection)
+    }
+
     /// Fetches and prints the full JSON response for a query
     /// Validates the query before sending to ensure correct syntax
     pub async fn print_full_json_response(&self, query: &str) -> Result<(), ScryfallError> {
         // Validate query before sending
-        self.validator.validate(query)?;
-
-        let encoded_query = self.validator.encode_query(query);
-        let url = format!(
-            "https://api.scryfall.com/cards/search?q={}",
-            encoded_query
-        );
+        let encoded_query = self.validator.validate_and_encode(query)?;
+        let url = self.search_url(&encoded_query);
 
         let json = self.fetch_json_page(&url).await?;
         println!("Query: {}", query);
@@ -97,145 +716,298 @@ impl ScryfallClient {
         Ok(())
     }
 
+    /// Fetch a single page of search results, leaving pagination to the caller (e.g. an
+    /// infinite-scroll UI). With `page_url` unset this validates `query` and fetches page 1;
+    /// otherwise it fetches `page_url`, normally a previous page's `next_page`, and `query`
+    /// is not used.
+    pub async fn search_page(
+        &self,
+        query: &str,
+        page_url: Option<&str>,
+    ) -> Result<SearchPage, ScryfallError> {
+        let url = match page_url {
+            Some(url) => url.to_string(),
+            None => self.search_url(&self.validator.validate_and_encode(query)?),
+        };
+        let response = self.fetch_page(&url).await?;
+
+        Ok(SearchPage {
+            cards: response.data,
+            total_cards: response.total_cards,
+            has_more: response.has_more,
+            next_page: response.next_page.filter(|_| response.has_more),
+            warnings: response.warnings,
+        })
+    }
+
     /// Fetches all pages of JSON data for a query and returns them
-    /// Validates the query before sending to ensure correct syntax
+    /// Validates the query before sending to ensure correct syntax.
+    /// `limits` can cap the number of pages or cards; whole pages are kept, so `max_cards`
+    /// may be exceeded by up to one page.
     pub async fn fetch_all_json(
         &self,
         query: &str,
-    ) -> Result<Vec<serde_json::Value>, ScryfallError> {
-        // Validate query before sending
-        self.validator.validate(query)?;
+        limits: FetchLimits,
+    ) -> Result<FetchOutcome<Vec<serde_json::Value>>, ScryfallError> {
+        self.fetch_all_json_with_progress(query, limits, Progress::print)
+            .await
+    }
 
-        let encoded_query = self.validator.encode_query(query);
+    /// Same as [`fetch_all_json`](Self::fetch_all_json), but reports progress through
+    /// `progress` instead of printing to stdout
+    pub async fn fetch_all_json_with_progress(
+        &self,
+        query: &str,
+        limits: FetchLimits,
+        progress: impl Fn(Progress),
+    ) -> Result<FetchOutcome<Vec<serde_json::Value>>, ScryfallError> {
+        // Validate query before sending
+        let encoded_query = self.validator.validate_and_encode(query)?;
         let mut all_pages: Vec<serde_json::Value> = Vec::new();
-        let mut next_url: Option<String> = Some(format!(
-            "https://api.scryfall.com/cards/search?q={}",
-            encoded_query
-        ));
+        let mut next_url: Option<String> = Some(self.search_url(&encoded_query));
 
         let mut page = 1;
+        let mut card_total = 0;
+        let mut truncated = false;
+        let mut warnings = Vec::new();
         let start = std::time::Instant::now();
 
         while let Some(url) = next_url {
-            println!("Fetching page {}...", page);
+            if self.cancel.is_cancelled() {
+                truncated = true;
+                break;
+            }
+
+            progress(Progress::FetchingPage { page });
 
             let json = self.fetch_json_page(&url).await?;
 
             let card_count = json["data"].as_array().map(|a| a.len()).unwrap_or(0);
             let total = json["total_cards"].as_u64().unwrap_or(0);
+            card_total += card_count;
 
-            println!(
-                "  Got {} cards (total: {}) [{:.2}s elapsed]",
-                card_count,
-                total,
-                start.elapsed().as_secs_f64()
-            );
+            progress(Progress::PageFetched {
+                page,
+                cards: card_count,
+                total_cards: total,
+                elapsed: start.elapsed(),
+            });
 
             let has_more = json["has_more"].as_bool().unwrap_or(false);
             let next_page = json["next_page"].as_str().map(|s| s.to_string());
 
+            add_warnings(&mut warnings, json_warnings(&json));
             all_pages.push(json);
 
             next_url = if has_more { next_page } else { None };
+            if next_url.is_some() && limits.reached(page, card_total) {
+                truncated = true;
+                break;
+            }
             page += 1;
         }
 
-        Ok(all_pages)
+        Ok(FetchOutcome {
+            data: all_pages,
+            truncated,
+            warnings,
+        })
     }
 
     /// Fetches all pages of JSON data for a query and stores them in the database immediately
     /// as each page is fetched. This ensures data is persisted even if the process is interrupted.
-    /// Returns the total number of cards stored.
+    /// Returns how many cards were stored, out of how many matched, over how many pages.
+    /// `limits` can cap the number of pages or cards; whole pages are stored, so `max_cards`
+    /// may be exceeded by up to one page.
     pub async fn fetch_and_store(
         &self,
         query: &str,
         db: &Database,
-    ) -> Result<usize, ScryfallError> {
-        // Validate query before sending
-        self.validator.validate(query)?;
+        limits: FetchLimits,
+    ) -> Result<FetchOutcome<StoreSummary>, ScryfallError> {
+        self.fetch_and_store_with_progress(query, db, limits, Progress::print)
+            .await
+    }
 
-        let encoded_query = self.validator.encode_query(query);
-        let mut next_url: Option<String> = Some(format!(
-            "https://api.scryfall.com/cards/search?q={}",
-            encoded_query
-        ));
+    /// Same as [`fetch_and_store`](Self::fetch_and_store), but reports progress through
+    /// `progress` instead of printing to stdout
+    pub async fn fetch_and_store_with_progress(
+        &self,
+        query: &str,
+        db: &Database,
+        limits: FetchLimits,
+        progress: impl Fn(Progress),
+    ) -> Result<FetchOutcome<StoreSummary>, ScryfallError> {
+        // Validate query before sending
+        let encoded_query = self.validator.validate_and_encode(query)?;
+        let mut next_url: Option<String> = Some(self.search_url(&encoded_query));
 
         let mut page = 1;
-        let mut total_stored = 0;
+        let mut summary = StoreSummary::default();
+        let mut truncated = false;
+        let mut warnings = Vec::new();
         let start = std::time::Instant::now();
 
         while let Some(url) = next_url {
-            println!("Fetching page {}...", page);
+            if self.cancel.is_cancelled() {
+                progress(Progress::Cancelled {
+                    stored: summary.stored,
+                });
+                truncated = true;
+                break;
+            }
+
+            progress(Progress::FetchingPage { page });
 
             let json = self.fetch_json_page(&url).await?;
 
             let card_count = json["data"].as_array().map(|a| a.len()).unwrap_or(0);
             let total = json["total_cards"].as_u64().unwrap_or(0);
+            if page == 1 {
+                summary.total_cards = total;
+            }
 
             // Store cards immediately after fetching this page
             let stored = db
                 .upsert_cards_from_response(&json)
                 .await
                 .map_err(|e| ScryfallError::DatabaseError(e.to_string()))?;
-            total_stored += stored;
+            summary.stored += stored;
+            summary.pages = page;
+            add_warnings(&mut warnings, json_warnings(&json));
 
-            println!(
-                "  Got {} cards, stored {} (total: {}) [{:.2}s elapsed]",
-                card_count,
+            progress(Progress::PageStored {
+                page,
+                cards: card_count,
                 stored,
-                total,
-                start.elapsed().as_secs_f64()
-            );
+                total_cards: total,
+                elapsed: start.elapsed(),
+            });
 
             let has_more = json["has_more"].as_bool().unwrap_or(false);
             let next_page = json["next_page"].as_str().map(|s| s.to_string());
 
             next_url = if has_more { next_page } else { None };
+            if next_url.is_some() && limits.reached(page, summary.stored) {
+                truncated = true;
+                break;
+            }
             page += 1;
         }
 
-        Ok(total_stored)
+        Ok(FetchOutcome {
+            data: summary,
+            truncated,
+            warnings,
+        })
     }
 
     /// Fetch all cards for a single query (paginated - must be sequential)
-    /// Validates the query before sending to ensure correct syntax
-    pub async fn fetch_all_cards(&self, query: &str) -> Result<Vec<Card>, ScryfallError> {
-        // Validate query before sending
-        self.validator.validate(query)?;
+    /// Validates the query before sending to ensure correct syntax.
+    /// `limits` can cap the number of pages or cards; `max_cards` is exact.
+    pub async fn fetch_all_cards(
+        &self,
+        query: &str,
+        limits: FetchLimits,
+    ) -> Result<FetchOutcome<Vec<Card>>, ScryfallError> {
+        self.fetch_all_cards_with_progress(query, limits, Progress::print)
+            .await
+    }
 
-        let encoded_query = self.validator.encode_query(query);
+    /// Same as [`fetch_all_cards`](Self::fetch_all_cards), but reports progress through
+    /// `progress` instead of printing to stdout
+    pub async fn fetch_all_cards_with_progress(
+        &self,
+        query: &str,
+        limits: FetchLimits,
+        progress: impl Fn(Progress),
+    ) -> Result<FetchOutcome<Vec<Card>>, ScryfallError> {
+        // Validate query before sending
+        let encoded_query = self.validator.validate_and_encode(query)?;
         let mut all_cards: Vec<Card> = Vec::new();
-        let mut next_url: Option<String> = Some(format!(
-            "https://api.scryfall.com/cards/search?q={}",
-            encoded_query
-        ));
+        let mut next_url: Option<String> = Some(self.search_url(&encoded_query));
 
         let mut page = 1;
+        let mut truncated = false;
+        let mut warnings = Vec::new();
         let start = Instant::now();
 
         while let Some(url) = next_url {
-            println!("Fetching page {}...", page);
+            if self.cancel.is_cancelled() {
+                truncated = true;
+                break;
+            }
+
+            progress(Progress::FetchingPage { page });
 
             let search_result = self.fetch_page(&url).await?;
 
-            println!(
-                "  Got {} cards (total: {}) [{:.2}s elapsed]",
-                search_result.data.len(),
-                search_result.total_cards,
-                start.elapsed().as_secs_f64()
-            );
+            progress(Progress::PageFetched {
+                page,
+                cards: search_result.data.len(),
+                total_cards: search_result.total_cards.into(),
+                elapsed: start.elapsed(),
+            });
 
             all_cards.extend(search_result.data);
+            add_warnings(&mut warnings, search_result.warnings);
 
             next_url = if search_result.has_more {
-                page += 1;
                 search_result.next_page
             } else {
                 None
             };
+
+            if let Some(max) = limits.max_cards.filter(|max| all_cards.len() > *max) {
+                all_cards.truncate(max);
+                truncated = true;
+                break;
+            }
+            if next_url.is_some() && limits.reached(page, all_cards.len()) {
+                truncated = true;
+                break;
+            }
+            page += 1;
         }
 
-        Ok(all_cards)
+        Ok(FetchOutcome {
+            data: all_cards,
+            truncated,
+            warnings,
+        })
+    }
+
+    /// Stream the cards for a query as each page arrives instead of collecting them first.
+    /// The query is validated before the stream is returned; request errors are yielded as
+    /// items and end the stream. Cancellation stops the stream between pages.
+    pub fn fetch_card_stream(
+        &self,
+        query: &str,
+    ) -> Result<impl Stream<Item = Result<Card, ScryfallError>> + '_, ScryfallError> {
+        let encoded_query = self.validator.validate_and_encode(query)?;
+        let first_url = self.search_url(&encoded_query);
+
+        let pages = stream::try_unfold(Some(first_url), move |next_url| async move {
+            let Some(url) = next_url else {
+                return Ok(None);
+            };
+            if self.cancel.is_cancelled() {
+                return Ok(None);
+            }
+
+            let search_result = self.fetch_page(&url).await?;
+            let next_url = if search_result.has_more {
+                search_result.next_page
+            } else {
+                None
+            };
+
+            Ok::<_, ScryfallError>(Some((search_result.data, next_url)))
+        });
+
+        Ok(pages
+            .map_ok(|cards| stream::iter(cards.into_iter().map(Ok)))
+            .try_flatten())
     }
 
     /// Validate multiple queries without sending them
@@ -250,18 +1022,64 @@ impl ScryfallClient {
             .collect()
     }
 
+    /// Validate multiple queries and show the search URL each would start from, without any
+    /// network I/O, e.g. to confirm a `fetch_multiple_queries` run before spending rate budget
+    pub fn plan_queries(&self, queries: &[&str]) -> Vec<QueryPlan> {
+        queries
+            .iter()
+            .map(|query| match self.validator.validate_and_encode(query) {
+                Ok(encoded) => QueryPlan {
+                    query: query.to_string(),
+                    valid: true,
+                    error: None,
+                    encoded_url: Some(self.search_url(&encoded)),
+                },
+                Err(e) => QueryPlan {
+                    query: query.to_string(),
+                    valid: false,
+                    error: Some(e),
+                    encoded_url: None,
+                },
+            })
+            .collect()
+    }
+
     /// Downloads the complete Scryfall card database via the bulk data API and stores all cards.
     /// This is significantly faster than paginated search queries and guarantees complete coverage
     /// of every card (all printings, all layouts, all edge cases).
+    ///
+    /// The catalog and download are requested conditionally using the validators from the last
+    /// successful import (kept in `db_metadata`), so an unchanged snapshot returns [`BulkImport::UpToDate`] without
+    /// downloading anything. Into an empty `cards` table the snapshot is loaded with `COPY`;
+    /// otherwise it is upserted in batches.
     pub async fn download_and_store_bulk(
         &self,
         db: &Database,
-    ) -> Result<usize, ScryfallError> {
+    ) -> Result<BulkImport, ScryfallError> {
+        self.download_and_store_bulk_with(db, &BulkImportOptions::default(), Progress::print)
+            .await
+    }
+
+    /// Same as [`download_and_store_bulk`](Self::download_and_store_bulk), with import
+    /// `options` and progress reported through `progress` instead of printed to stdout
+    pub async fn download_and_store_bulk_with(
+        &self,
+        db: &Database,
+        options: &BulkImportOptions,
+        progress: impl Fn(Progress),
+    ) -> Result<BulkImport, ScryfallError> {
+        let cache = self.load_bulk_cache_state(db).await?;
+
         // 1. Fetch bulk data catalog from Scryfall API (rate limited)
-        println!("Fetching bulk data catalog...");
-        let catalog = self
-            .fetch_json_page("https://api.scryfall.com/bulk-data")
-            .await?;
+        progress(Progress::FetchingCatalog);
+        let catalog_url = format!("{}/bulk-data", self.base_url);
+        let Some((catalog, catalog_validators)) = self
+            .fetch_json_page_if_modified(&catalog_url, &cache.catalog)
+            .await?
+        else {
+            progress(Progress::UpToDate);
+            return Ok(BulkImport::UpToDate);
+        };
 
         // 2. Find the default_cards entry (every card printing, excludes extras like tokens/art)
         let bulk_entry = catalog["data"]
@@ -281,115 +1099,244 @@ impl ScryfallClient {
         })?;
         let updated_at = bulk_entry["updated_at"].as_str().unwrap_or("unknown");
 
-        println!("Bulk data last updated: {}", updated_at);
-        println!("Downloading: {}", download_uri);
+        progress(Progress::BulkDataFound {
+            updated_at: updated_at.to_string(),
+            download_uri: download_uri.to_string(),
+        });
 
         // 3. Download with a dedicated client (longer timeout, no rate limiting needed
         //    since bulk data is served from a CDN on a different domain)
+        //    The CDN may compress the file, so let reqwest decode gzip/brotli/deflate transparently.
         let bulk_client = reqwest::Client::builder()
             .timeout(Duration::from_secs(600))
             .connect_timeout(Duration::from_secs(30))
+            .gzip(true)
+            .brotli(true)
+            .deflate(true)
             .build()
             .map_err(|e| ScryfallError::RequestError(Arc::new(e)))?;
 
-        let mut response = bulk_client
+        // Only revalidate the download if it is the same file we imported last time
+        let download_validators = if cache.download_uri.as_deref() == Some(download_uri) {
+            cache.download.conditional_headers()
+        } else {
+            HeaderMap::new()
+        };
+
+        let response = bulk_client
             .get(download_uri)
             .headers(self.headers.clone())
+            .headers(download_validators)
             .send()
-            .await?
-            .error_for_status()?;
+            .await?;
+
+        if response.status() == StatusCode::NOT_MODIFIED {
+            self.save_bulk_cache_state(
+                db,
+                BulkCacheState {
+                    catalog: catalog_validators,
+                    ..cache
+                },
+            )
+            .await?;
+            progress(Progress::UpToDate);
+            return Ok(BulkImport::UpToDate);
+        }
+
+        let mut response = check_status(response).await?;
+        let new_download_validators = CacheValidators::from_headers(response.headers());
+
+        // reqwest removes Content-Encoding once it has decoded the body, so anything still
+        // set here is an encoding we can't decode
+        let header_str = |name| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|v: &HeaderValue| v.to_str().ok())
+                .map(|s| s.to_string())
+        };
+        let content_type = header_str(CONTENT_TYPE);
+        let content_encoding = header_str(CONTENT_ENCODING);
+        if let Some(encoding) = &content_encoding {
+            return Err(ScryfallError::DatabaseError(format!(
+                "Bulk download uses unsupported Content-Encoding '{}'",
+                encoding
+            )));
+        }
+        if let Some(ct) = content_type
+            .as_deref()
+            .filter(|ct| !ct.contains("json") && !ct.contains("octet-stream"))
+        {
+            return Err(ScryfallError::DatabaseError(format!(
+                "Bulk download has Content-Type '{}', expected JSON",
+                ct
+            )));
+        }
 
         let content_length = response.content_length();
         let mut bytes: Vec<u8> = Vec::new();
         if let Some(total) = content_length {
             bytes.reserve(total as usize);
-            println!(
-                "Download size: {:.1} MB",
-                total as f64 / 1_048_576.0
-            );
         }
+        progress(Progress::DownloadStarted {
+            total_bytes: content_length,
+        });
 
         let download_start = Instant::now();
         let mut downloaded: u64 = 0;
         let mut last_report = Instant::now();
 
         while let Some(chunk) = response.chunk().await? {
+            if self.cancel.is_cancelled() {
+                progress(Progress::Cancelled { stored: 0 });
+                return Ok(BulkImport::Cancelled(0));
+            }
+
             downloaded += chunk.len() as u64;
             bytes.extend_from_slice(&chunk);
 
             if last_report.elapsed() > Duration::from_millis(500) {
-                if let Some(total) = content_length {
-                    let pct = (downloaded as f64 / total as f64) * 100.0;
-                    print!(
-                        "\rDownloading: {:.1}/{:.1} MB ({:.1}%)",
-                        downloaded as f64 / 1_048_576.0,
-                        total as f64 / 1_048_576.0,
-                        pct
-                    );
-                } else {
-                    print!(
-                        "\rDownloading: {:.1} MB",
-                        downloaded as f64 / 1_048_576.0
-                    );
-                }
-                std::io::stdout().flush().ok();
+                progress(Progress::Downloading {
+                    downloaded,
+                    total: content_length,
+                });
                 last_report = Instant::now();
             }
         }
-        println!(
-            "\nDownload complete in {:.1}s ({:.1} MB)",
-            download_start.elapsed().as_secs_f64(),
-            downloaded as f64 / 1_048_576.0
-        );
+        progress(Progress::DownloadComplete {
+            bytes: downloaded,
+            elapsed: download_start.elapsed(),
+        });
 
         // 4. Parse the JSON array (all cards in one array)
-        println!("Parsing JSON...");
+        progress(Progress::Parsing);
         let parse_start = Instant::now();
+        check_bulk_payload(&bytes)?;
         let cards: Vec<serde_json::Value> = serde_json::from_slice(&bytes).map_err(|e| {
             ScryfallError::DatabaseError(format!("Failed to parse bulk data JSON: {}", e))
         })?;
         drop(bytes); // Free download buffer
-        println!(
-            "Parsed {} cards in {:.1}s",
-            cards.len(),
-            parse_start.elapsed().as_secs_f64()
-        );
+        progress(Progress::Parsed {
+            cards: cards.len(),
+            elapsed: parse_start.elapsed(),
+        });
 
-        // 5. Batch upsert into database (500 cards per transaction)
+        // 5. Store the cards: COPY into an empty table, otherwise batch upserts (`batch_size`
+        // cards per COPY or transaction, `concurrency` upsert transactions at a time)
         let total = cards.len();
         let mut stored: usize = 0;
-        let batch_size = 500;
         let store_start = Instant::now();
 
-        for chunk in cards.chunks(batch_size) {
-            let batch_stored = db
-                .upsert_cards_batch(chunk)
+        let table_empty = db
+            .get_card_count()
+            .await
+            .map_err(|e| ScryfallError::DatabaseError(e.to_string()))?
+            == 0;
+
+        if table_empty {
+            // One COPY per chunk, so the token is checked between chunks as on the upsert path
+            for chunk in cards.chunks(options.batch_size.max(1)) {
+                if self.cancel.is_cancelled() {
+                    progress(Progress::Cancelled { stored });
+                    return Ok(BulkImport::Cancelled(stored));
+                }
+
+                stored += db
+                    .copy_cards(chunk)
+                    .await
+                    .map_err(|e| ScryfallError::DatabaseError(e.to_string()))?;
+
+                progress(Progress::Storing {
+                    stored,
+                    total,
+                    elapsed: store_start.elapsed(),
+                });
+            }
+        } else {
+            // A batch that sees the token fired is skipped; batches already running finish, so
+            // `stored` counts exactly the committed cards
+            let mut batches = stream::iter(cards.chunks(options.batch_size.max(1)))
+                .map(|chunk| async move {
+                    if self.cancel.is_cancelled() {
+                        return Ok(None);
+                    }
+                    db.upsert_cards_batch(chunk).await.map(Some)
+                })
+                .buffer_unordered(options.concurrency.max(1));
+
+            let mut cancelled = false;
+            while let Some(result) = batches.next().await {
+                match result.map_err(|e| ScryfallError::DatabaseError(e.to_string()))? {
+                    Some(batch_stored) => {
+                        stored += batch_stored;
+                        progress(Progress::Storing {
+                            stored,
+                            total,
+                            elapsed: store_start.elapsed(),
+                        });
+                    }
+                    None => cancelled = true,
+                }
+            }
+
+            if cancelled {
+                progress(Progress::Cancelled { stored });
+                return Ok(BulkImport::Cancelled(stored));
+            }
+        }
+        progress(Progress::StoreComplete {
+            stored,
+            elapsed: store_start.elapsed(),
+        });
+
+        if options.prune_missing {
+            let ids: HashSet<String> = cards
+                .iter()
+                .filter_map(|card| card["id"].as_str().map(|s| s.to_string()))
+                .collect();
+            let card_count = db
+                .get_card_count()
                 .await
                 .map_err(|e| ScryfallError::DatabaseError(e.to_string()))?;
-            stored += batch_stored;
 
-            let elapsed = store_start.elapsed().as_secs_f64();
-            let rate = if elapsed > 0.0 {
-                stored as f64 / elapsed
+            // A snapshot much smaller than the table is more likely truncated than a real
+            // mass removal, so keep the existing cards
+            if ids.is_empty() || (ids.len() as f64) < card_count as f64 * PRUNE_MIN_RATIO {
+                progress(Progress::PruneSkipped {
+                    snapshot: ids.len(),
+                    card_count,
+                });
             } else {
-                0.0
-            };
-            print!(
-                "\rStoring: {}/{} ({:.1}%) - {:.0} cards/sec",
-                stored,
-                total,
-                (stored as f64 / total as f64) * 100.0,
-                rate
-            );
-            std::io::stdout().flush().ok();
-        }
-        println!(
-            "\nStored {} cards in {:.1}s",
-            stored,
-            store_start.elapsed().as_secs_f64()
-        );
+                let deleted = db
+                    .delete_cards_not_in(&ids)
+                    .await
+                    .map_err(|e| ScryfallError::DatabaseError(e.to_string()))?;
+                progress(Progress::Pruned { deleted });
+            }
+        }
 
-        Ok(stored)
+        db.record_bulk_import(updated_at, stored)
+            .await
+            .map_err(|e| ScryfallError::DatabaseError(e.to_string()))?;
+
+        progress(Progress::Analyzing);
+        db.analyze()
+            .await
+            .map_err(|e| ScryfallError::DatabaseError(e.to_string()))?;
+
+        // Only remember validators once the snapshot is fully stored, so a failed import is
+        // retried in full next time
+        self.save_bulk_cache_state(
+            db,
+            BulkCacheState {
+                catalog: catalog_validators,
+                download_uri: Some(download_uri.to_string()),
+                download: new_download_validators,
+            },
+        )
+        .await?;
+
+        Ok(BulkImport::Stored(stored))
     }
 
     /// Fetch multiple queries concurrently (rate-limited)
@@ -424,7 +1371,11 @@ impl ScryfallClient {
         // Execute valid queries
         let futures: Vec<_> = valid_queries
             .iter()
-            .map(|(_, query)| self.fetch_all_cards(query))
+            .map(|(_, query)| async move {
+                self.fetch_all_cards(query, FetchLimits::none())
+                    .await
+                    .map(|outcome| outcome.data)
+            })
             .collect();
 
         let fetch_results = futures::future::join_all(futures).await;
@@ -447,6 +1398,114 @@ impl ScryfallClient {
 
         results
     }
+
+    /// Like [`fetch_multiple_queries`](Self::fetch_multiple_queries), but merges all results
+    /// into a single list with duplicate cards (by Scryfall `id`) removed, keeping the order
+    /// in which cards were first seen. Failed queries are reported in `failures`.
+    pub async fn fetch_multiple_queries_deduped(&self, queries: Vec<&str>) -> DedupedCards {
+        let results = self.fetch_multiple_queries(queries.clone()).await;
+
+        let mut seen = HashSet::new();
+        let mut deduped = DedupedCards::default();
+
+        for (query, result) in queries.into_iter().zip(results) {
+            match result {
+                Ok(cards) => deduped.cards.extend(
+                    cards
+                        .into_iter()
+                        .filter(|card| seen.insert(card.id.clone())),
+                ),
+                Err(e) => deduped.failures.push((query.to_string(), e)),
+            }
+        }
+
+        deduped
+    }
+}
+
+/// Sanity-checks a downloaded bulk file before parsing, so a compressed or non-JSON body
+/// produces a clear error instead of a cryptic serde message
+fn check_bulk_payload(bytes: &[u8]) -> Result<(), ScryfallError> {
+    if bytes.starts_with(&[0x1f, 0x8b]) {
+        return Err(ScryfallError::DatabaseError(
+            "Bulk download is still gzip-compressed; the server sent it without a matching \
+             Content-Encoding header"
+                .into(),
+        ));
+    }
+
+    match bytes.iter().find(|b| !b.is_ascii_whitespace()) {
+        Some(b'[') => Ok(()),
+        Some(&first) => Err(ScryfallError::DatabaseError(format!(
+            "Bulk download does not look like a JSON array (starts with byte 0x{:02x})",
+            first
+        ))),
+        None => Err(ScryfallError::DatabaseError(
+            "Bulk download is empty".into(),
+        )),
+    }
+}
+
+/// Passes successful responses through and turns any other status into a structured error,
+/// keeping the `details`/`warnings` from Scryfall's JSON error body when there is one.
+/// A 404 becomes `ScryfallError::NotFound`.
+async fn check_status(response: reqwest::Response) -> Result<reqwest::Response, ScryfallError> {
+    let status = response.status();
+    if status.is_success() || status == StatusCode::NOT_MODIFIED {
+        return Ok(response);
+    }
+
+    let body: serde_json::Value = response.json().await.unwrap_or_default();
+    let details = body["details"]
+        .as_str()
+        .map(|s| s.to_string())
+        .unwrap_or_else(|| {
+            status
+                .canonical_reason()
+                .unwrap_or("Unknown error")
+                .to_string()
+        });
+
+    if status == StatusCode::NOT_FOUND {
+        return Err(ScryfallError::NotFound(details));
+    }
+
+    let warnings = json_warnings(&body);
+
+    Err(ScryfallError::ApiError {
+        status: status.as_u16(),
+        details,
+        warnings,
+    })
+}
+
+/// The `warnings` of a search page or error body, if any
+fn json_warnings(page: &serde_json::Value) -> Vec<String> {
+    page["warnings"]
+        .as_array()
+        .map(|warnings| {
+            warnings
+                .iter()
+                .filter_map(|w| w.as_str().map(String::from))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Append a page's warnings to `all`, skipping ones already seen on earlier pages
+fn add_warnings(all: &mut Vec<String>, page_warnings: Vec<String>) {
+    for warning in page_warnings {
+        if !all.contains(&warning) {
+            all.push(warning);
+        }
+    }
+}
+
+/// Parse a numeric environment variable, naming it in the error
+fn parse_env_var<T: std::str::FromStr>(name: &str, value: &str) -> Result<T, ScryfallError> {
+    value.trim().parse().map_err(|_| {
+        ScryfallError::InvalidConfig(format!("{} must be a whole number, got '{}'", name, value))
+    })
 }
 
 impl Default for ScryfallClient {
@@ -1,7 +1,33 @@
-use sqlx::postgres::PgPoolOptions;
+use futures::{Stream, StreamExt};
+use sqlx::postgres::{PgArguments, PgPoolCopyExt, PgPoolOptions};
+use sqlx::query::Query;
 use sqlx::{PgConnection, Pool, Postgres};
+use std::collections::HashSet;
 use std::env;
 
+/// `db_metadata` key holding the Scryfall `updated_at` of the last imported bulk snapshot
+pub const BULK_UPDATED_AT_KEY: &str = "bulk_updated_at";
+/// `db_metadata` key holding when the last bulk import completed
+pub const BULK_DOWNLOADED_AT_KEY: &str = "bulk_downloaded_at";
+/// `db_metadata` key holding the number of cards in the last bulk import
+pub const BULK_CARD_COUNT_KEY: &str = "bulk_card_count";
+/// `db_metadata` key holding the HTTP cache validators of the last bulk import, as JSON
+pub const BULK_CACHE_KEY: &str = "bulk_cache_validators";
+
+/// How much CSV `copy_cards` buffers before sending it to the server
+const COPY_CHUNK_BYTES: usize = 1 << 20;
+
+/// How [`Database::search_by_colors`] compares a card's colors with the requested ones
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorMatch {
+    /// Exactly these colors, e.g. mono-red for `["R"]`
+    Exact,
+    /// These colors and possibly more, e.g. any red card for `["R"]`
+    AtLeast,
+    /// No colors outside these, e.g. red or colorless cards for `["R"]`
+    AtMost,
+}
+
 pub struct Database {
     pool: Pool<Postgres>,
 }
@@ -10,10 +36,14 @@ impl Database {
     /// Create a new database connection using DATABASE_URL environment variable
     pub async fn new() -> Result<Self, sqlx::Error> {
         let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        Self::connect(&database_url).await
+    }
 
+    /// Create a new database connection to `database_url`, bringing its schema up to date
+    pub async fn connect(database_url: &str) -> Result<Self, sqlx::Error> {
         let pool = PgPoolOptions::new()
             .max_connections(10)
-            .connect(&database_url)
+            .connect(database_url)
             .await?;
 
         let db = Self { pool };
@@ -89,7 +119,7 @@ impl Database {
                 image_uris TEXT,
                 card_faces TEXT,
                 all_parts TEXT,
-                raw_json TEXT NOT NULL,
+                raw_json JSONB NOT NULL,
                 created_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP,
                 updated_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP
             )
@@ -98,6 +128,23 @@ impl Database {
         .execute(&self.pool)
         .await?;
 
+        // Older schemas stored raw_json as TEXT; convert it once so JSON operators work on it
+        sqlx::query(
+            r#"
+            DO $$
+            BEGIN
+                IF (SELECT data_type FROM information_schema.columns
+                    WHERE table_schema = current_schema() AND table_name = 'cards'
+                      AND column_name = 'raw_json') = 'text' THEN
+                    ALTER TABLE cards ALTER COLUMN raw_json TYPE JSONB USING raw_json::jsonb;
+                END IF;
+            END
+            $$
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
         // Add columns that may not exist in older schemas
         sqlx::query("ALTER TABLE cards ADD COLUMN IF NOT EXISTS loyalty TEXT")
             .execute(&self.pool)
@@ -106,6 +153,46 @@ impl Database {
             .execute(&self.pool)
             .await?;
 
+        // Prices parsed out of the `prices` JSON so they can be compared numerically
+        for column in PRICE_COLUMNS {
+            sqlx::query(&format!(
+                "ALTER TABLE cards ADD COLUMN IF NOT EXISTS {} DOUBLE PRECISION",
+                column
+            ))
+            .execute(&self.pool)
+            .await?;
+        }
+
+        // Backfill the price columns of rows stored before they existed
+        sqlx::query(
+            r#"
+            UPDATE cards SET
+                price_usd = NULLIF(raw_json->'prices'->>'usd', '')::float8,
+                price_usd_foil = NULLIF(raw_json->'prices'->>'usd_foil', '')::float8,
+                price_eur = NULLIF(raw_json->'prices'->>'eur', '')::float8,
+                price_tix = NULLIF(raw_json->'prices'->>'tix', '')::float8
+            WHERE COALESCE(price_usd, price_usd_foil, price_eur, price_tix) IS NULL
+              AND COALESCE(
+                  raw_json->'prices'->>'usd', raw_json->'prices'->>'usd_foil',
+                  raw_json->'prices'->>'eur', raw_json->'prices'->>'tix'
+              ) IS NOT NULL
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        // Full-text search over name and rules text, kept in sync by Postgres
+        sqlx::query(
+            r#"
+            ALTER TABLE cards ADD COLUMN IF NOT EXISTS search_vector TSVECTOR
+            GENERATED ALWAYS AS (
+                to_tsvector('english', coalesce(name, '') || ' ' || coalesce(oracle_text, ''))
+            ) STORED
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
         // Create indexes for common queries
         sqlx::query("CREATE INDEX IF NOT EXISTS idx_cards_name ON cards(name)")
             .execute(&self.pool)
@@ -119,30 +206,159 @@ impl Database {
         sqlx::query("CREATE INDEX IF NOT EXISTS idx_cards_type_line ON cards(type_line)")
             .execute(&self.pool)
             .await?;
+        sqlx::query(
+            "CREATE INDEX IF NOT EXISTS idx_cards_raw_json ON cards USING GIN (raw_json jsonb_path_ops)",
+        )
+        .execute(&self.pool)
+        .await?;
+        sqlx::query(
+            "CREATE INDEX IF NOT EXISTS idx_cards_search_vector ON cards USING GIN (search_vector)",
+        )
+        .execute(&self.pool)
+        .await?;
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_cards_price_usd ON cards(price_usd)")
+            .execute(&self.pool)
+            .await?;
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS rulings (
+                id SERIAL PRIMARY KEY,
+                card_id TEXT NOT NULL,
+                oracle_id TEXT,
+                source TEXT,
+                published_at TEXT,
+                comment TEXT NOT NULL,
+                created_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_rulings_card_id ON rulings(card_id)")
+            .execute(&self.pool)
+            .await?;
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS sets (
+                code TEXT PRIMARY KEY,
+                id TEXT,
+                name TEXT NOT NULL,
+                set_type TEXT,
+                released_at TEXT,
+                card_count INTEGER,
+                parent_set_code TEXT,
+                digital BOOLEAN,
+                icon_svg_uri TEXT,
+                raw_json JSONB NOT NULL,
+                updated_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS db_metadata (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL,
+                updated_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
 
         Ok(())
     }
 
-    /// Insert or update a single card from raw JSON
+    /// Insert or update a single card from raw JSON. Fails with `InvalidArgument` if the card
+    /// has no `id`, rather than storing it under an empty primary key.
     pub async fn upsert_card(&self, card_json: &serde_json::Value) -> Result<(), sqlx::Error> {
         let mut conn = self.pool.acquire().await?;
         execute_card_upsert(&mut conn, card_json).await
     }
 
-    /// Insert or update a batch of cards within a single transaction
+    /// Insert or update a batch of cards within a single transaction. Cards without an `id`
+    /// are skipped; the returned count is the cards actually stored, so
+    /// `cards.len() - stored` were skipped.
     pub async fn upsert_cards_batch(
         &self,
         cards: &[serde_json::Value],
     ) -> Result<usize, sqlx::Error> {
         let mut tx = self.pool.begin().await?;
-        for card in cards {
-            execute_card_upsert(&mut *tx, card).await?;
+        let mut stored = 0;
+        for card in cards.iter().filter(|card| card_id(card).is_ok()) {
+            execute_card_upsert(&mut tx, card).await?;
+            stored += 1;
         }
         tx.commit().await?;
-        Ok(cards.len())
+        Ok(stored)
+    }
+
+    /// Upsert cards as they arrive from `cards` (a file parser, an API stream, ...) without
+    /// collecting them first: every `batch_size` cards are stored in their own transaction,
+    /// so at most one batch is held in memory and earlier batches stay committed if a later
+    /// one fails. Cards without an `id` are skipped; returns the number stored.
+    pub async fn upsert_cards_stream<S>(
+        &self,
+        cards: S,
+        batch_size: usize,
+    ) -> Result<usize, sqlx::Error>
+    where
+        S: Stream<Item = serde_json::Value>,
+    {
+        let mut batches = std::pin::pin!(cards.chunks(batch_size.max(1)));
+        let mut stored = 0;
+        while let Some(batch) = batches.next().await {
+            stored += self.upsert_cards_batch(&batch).await?;
+        }
+        Ok(stored)
+    }
+
+    /// Load cards with a single `COPY ... FROM STDIN`, which is much faster than row-by-row
+    /// upserts for a cold import. `COPY` cannot resolve conflicts, so when any of the cards is
+    /// already stored this falls back to `upsert_cards_batch`. The whole load is one
+    /// statement: if any row fails (e.g. a duplicate id in `cards`), nothing is stored. Cards
+    /// without an `id` are skipped, as in `upsert_cards_batch`.
+    pub async fn copy_cards(&self, cards: &[serde_json::Value]) -> Result<usize, sqlx::Error> {
+        let ids: Vec<&str> = cards.iter().filter_map(|card| card_id(card).ok()).collect();
+        let (any_stored,): (bool,) =
+            sqlx::query_as("SELECT EXISTS (SELECT 1 FROM cards WHERE id = ANY($1))")
+                .bind(&ids)
+                .fetch_one(&self.pool)
+                .await?;
+        if any_stored {
+            return self.upsert_cards_batch(cards).await;
+        }
+
+        let mut copy = self
+            .pool
+            .copy_in_raw(&format!(
+                "COPY cards ({}) FROM STDIN WITH (FORMAT csv)",
+                CARD_COLUMNS
+            ))
+            .await?;
+
+        let mut buffer = String::new();
+        for card in cards.iter().filter(|card| card_id(card).is_ok()) {
+            write_card_csv(&mut buffer, card);
+            if buffer.len() >= COPY_CHUNK_BYTES {
+                copy.send(buffer.as_bytes()).await?;
+                buffer.clear();
+            }
+        }
+        if !buffer.is_empty() {
+            copy.send(buffer.as_bytes()).await?;
+        }
+
+        let copied = copy.finish().await?;
+        Ok(copied as usize)
     }
 
-    /// Insert multiple cards from a search response
+    /// Insert multiple cards from a search response, skipping cards without an `id`
     pub async fn upsert_cards_from_response(
         &self,
         response: &serde_json::Value,
@@ -151,7 +367,7 @@ impl Database {
         let mut count = 0;
 
         if let Some(cards) = cards {
-            for card in cards {
+            for card in cards.iter().filter(|card| card_id(card).is_ok()) {
                 self.upsert_card(card).await?;
                 count += 1;
             }
@@ -160,6 +376,184 @@ impl Database {
         Ok(count)
     }
 
+    /// Replace the stored rulings for a card with `rulings` (ruling objects from Scryfall)
+    pub async fn upsert_rulings(
+        &self,
+        card_id: &str,
+        rulings: &[serde_json::Value],
+    ) -> Result<usize, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM rulings WHERE card_id = $1")
+            .bind(card_id)
+            .execute(&mut *tx)
+            .await?;
+
+        for ruling in rulings {
+            sqlx::query(
+                r#"
+                INSERT INTO rulings (card_id, oracle_id, source, published_at, comment)
+                VALUES ($1, $2, $3, $4, $5)
+                "#,
+            )
+            .bind(card_id)
+            .bind(ruling["oracle_id"].as_str())
+            .bind(ruling["source"].as_str())
+            .bind(ruling["published_at"].as_str())
+            .bind(ruling["comment"].as_str().unwrap_or_default())
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(rulings.len())
+    }
+
+    /// Get the stored rulings for a card, oldest first
+    pub async fn get_rulings(&self, card_id: &str) -> Result<Vec<serde_json::Value>, sqlx::Error> {
+        let rows: Vec<(serde_json::Value,)> = sqlx::query_as(
+            r#"
+            SELECT json_build_object(
+                'object', 'ruling',
+                'oracle_id', oracle_id,
+                'source', source,
+                'published_at', published_at,
+                'comment', comment
+            )
+            FROM rulings
+            WHERE card_id = $1
+            ORDER BY published_at, id
+            "#,
+        )
+        .bind(card_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|(json,)| json).collect())
+    }
+
+    /// Insert or update sets (set objects from Scryfall's `/sets`) keyed by set code.
+    /// Sets without a code are skipped; returns how many were stored.
+    pub async fn upsert_sets(&self, sets: &[serde_json::Value]) -> Result<usize, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        let mut stored = 0;
+
+        for set in sets {
+            let Some(code) = set["code"].as_str().filter(|code| !code.is_empty()) else {
+                continue;
+            };
+
+            sqlx::query(
+                r#"
+                INSERT INTO sets (
+                    code, id, name, set_type, released_at, card_count, parent_set_code,
+                    digital, icon_svg_uri, raw_json, updated_at
+                ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, CURRENT_TIMESTAMP)
+                ON CONFLICT(code) DO UPDATE SET
+                    id = EXCLUDED.id,
+                    name = EXCLUDED.name,
+                    set_type = EXCLUDED.set_type,
+                    released_at = EXCLUDED.released_at,
+                    card_count = EXCLUDED.card_count,
+                    parent_set_code = EXCLUDED.parent_set_code,
+                    digital = EXCLUDED.digital,
+                    icon_svg_uri = EXCLUDED.icon_svg_uri,
+                    raw_json = EXCLUDED.raw_json,
+                    updated_at = CURRENT_TIMESTAMP
+                "#,
+            )
+            .bind(code)
+            .bind(set["id"].as_str())
+            .bind(set["name"].as_str().unwrap_or_default())
+            .bind(set["set_type"].as_str())
+            .bind(set["released_at"].as_str())
+            .bind(set["card_count"].as_i64().map(|n| n as i32))
+            .bind(set["parent_set_code"].as_str())
+            .bind(set["digital"].as_bool())
+            .bind(set["icon_svg_uri"].as_str())
+            .bind(set)
+            .execute(&mut *tx)
+            .await?;
+            stored += 1;
+        }
+
+        tx.commit().await?;
+        Ok(stored)
+    }
+
+    /// Set a key in the `db_metadata` table
+    pub async fn set_metadata(&self, key: &str, value: &str) -> Result<(), sqlx::Error> {
+        let mut conn = self.pool.acquire().await?;
+        execute_metadata_upsert(&mut conn, key, value).await
+    }
+
+    /// Get a key from the `db_metadata` table
+    pub async fn get_metadata(&self, key: &str) -> Result<Option<String>, sqlx::Error> {
+        let row: Option<(String,)> = sqlx::query_as("SELECT value FROM db_metadata WHERE key = $1")
+            .bind(key)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(row.map(|(value,)| value))
+    }
+
+    /// Record which bulk snapshot the card data came from, when it was imported, and how many
+    /// cards it contained
+    pub async fn record_bulk_import(
+        &self,
+        updated_at: &str,
+        card_count: usize,
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        execute_metadata_upsert(&mut tx, BULK_UPDATED_AT_KEY, updated_at).await?;
+        execute_metadata_upsert(&mut tx, BULK_CARD_COUNT_KEY, &card_count.to_string()).await?;
+        sqlx::query(
+            r#"
+            INSERT INTO db_metadata (key, value, updated_at)
+            VALUES ($1, CURRENT_TIMESTAMP::text, CURRENT_TIMESTAMP)
+            ON CONFLICT(key) DO UPDATE SET
+                value = EXCLUDED.value,
+                updated_at = CURRENT_TIMESTAMP
+            "#,
+        )
+        .bind(BULK_DOWNLOADED_AT_KEY)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await
+    }
+
+    /// Refresh the query planner statistics for `cards`, e.g. after a bulk import, so the first
+    /// queries afterwards do not wait for autovacuum to pick good plans. Runs on its own pooled
+    /// connection, outside any transaction.
+    pub async fn analyze(&self) -> Result<(), sqlx::Error> {
+        let mut conn = self.pool.acquire().await?;
+        sqlx::query("ANALYZE cards").execute(&mut *conn).await?;
+        Ok(())
+    }
+
+    /// The Scryfall `updated_at` of the bulk snapshot last imported, if any
+    pub async fn get_bulk_version(&self) -> Result<Option<String>, sqlx::Error> {
+        self.get_metadata(BULK_UPDATED_AT_KEY).await
+    }
+
+    /// Delete every card whose id is not in `ids`, so the table mirrors a freshly imported
+    /// snapshot. Returns the number of deleted rows. An empty `ids` deletes nothing rather
+    /// than the whole table.
+    pub async fn delete_cards_not_in(&self, ids: &HashSet<String>) -> Result<u64, sqlx::Error> {
+        if ids.is_empty() {
+            return Ok(0);
+        }
+
+        let ids: Vec<&str> = ids.iter().map(String::as_str).collect();
+
+        let result = sqlx::query("DELETE FROM cards WHERE id <> ALL($1)")
+            .bind(ids)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
     /// Get total card count in database
     pub async fn get_card_count(&self) -> Result<i64, sqlx::Error> {
         let row: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM cards")
@@ -168,75 +562,330 @@ impl Database {
         Ok(row.0)
     }
 
-    /// Get a card by ID
+    /// Get a card by ID. `None` means there is no such row; a row whose `raw_json` is not a
+    /// card object is reported as a `Decode` error instead.
     pub async fn get_card_by_id(&self, id: &str) -> Result<Option<serde_json::Value>, sqlx::Error> {
-        let row: Option<(String,)> =
+        let row: Option<(serde_json::Value,)> =
             sqlx::query_as("SELECT raw_json FROM cards WHERE id = $1")
                 .bind(id)
                 .fetch_optional(&self.pool)
                 .await?;
 
-        Ok(row.map(|(json,)| serde_json::from_str(&json).unwrap_or_default()))
+        match row {
+            Some((json,)) if !json.is_object() => Err(sqlx::Error::Decode(
+                format!("card '{}' has corrupt raw_json: {}", id, json).into(),
+            )),
+            row => Ok(row.map(|(json,)| json)),
+        }
+    }
+
+    /// A random card, e.g. for a "show me a random card" button. `None` only when the table
+    /// is empty.
+    pub async fn random_card(&self) -> Result<Option<serde_json::Value>, sqlx::Error> {
+        let row: Option<(serde_json::Value,)> =
+            sqlx::query_as("SELECT raw_json FROM cards ORDER BY random() LIMIT 1")
+                .fetch_optional(&self.pool)
+                .await?;
+
+        Ok(row.map(|(json,)| json))
     }
 
     /// Search cards by name
     pub async fn search_by_name(&self, name: &str) -> Result<Vec<serde_json::Value>, sqlx::Error> {
-        let rows: Vec<(String,)> =
+        let rows: Vec<(serde_json::Value,)> =
             sqlx::query_as("SELECT raw_json FROM cards WHERE name ILIKE $1")
                 .bind(format!("%{}%", name))
                 .fetch_all(&self.pool)
                 .await?;
 
-        Ok(rows
-            .into_iter()
-            .filter_map(|(json,)| serde_json::from_str(&json).ok())
-            .collect())
+        Ok(rows.into_iter().map(|(json,)| json).collect())
     }
-}
 
-/// Execute a card upsert against any Postgres connection (pool connection or transaction).
-/// Handles all card layouts: normal, transform, modal_dfc, split, flip, adventure, meld, etc.
-async fn execute_card_upsert(
-    conn: &mut PgConnection,
-    card_json: &serde_json::Value,
-) -> Result<(), sqlx::Error> {
-    let id = card_json["id"].as_str().unwrap_or_default();
-    let raw_json = serde_json::to_string(card_json).unwrap_or_default();
+    /// One page of `search_by_name` results, ordered by name (then id, so pages are stable).
+    /// Pair with `count_by_name` for the total.
+    pub async fn search_by_name_paged(
+        &self,
+        name: &str,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<serde_json::Value>, sqlx::Error> {
+        let rows: Vec<(serde_json::Value,)> = sqlx::query_as(
+            "SELECT raw_json FROM cards WHERE name ILIKE $1 ORDER BY name, id LIMIT $2 OFFSET $3",
+        )
+        .bind(format!("%{}%", name))
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await?;
 
-    // For double-faced/split/adventure/flip cards, Scryfall puts per-face fields
-    // (mana_cost, oracle_text, power, toughness, loyalty, flavor_text, etc.)
-    // in card_faces[] instead of at the top level.
-    let front_face = card_json["card_faces"]
-        .as_array()
-        .and_then(|f| f.first());
+        Ok(rows.into_iter().map(|(json,)| json).collect())
+    }
 
-    // Helper: get a string field, falling back to front face for multi-face cards
-    let get_str = |field: &str| -> Option<&str> {
-        card_json[field]
-            .as_str()
-            .or_else(|| front_face.and_then(|f| f[field].as_str()))
-    };
+    /// Number of cards `search_by_name` would return
+    pub async fn count_by_name(&self, name: &str) -> Result<i64, sqlx::Error> {
+        let row: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM cards WHERE name ILIKE $1")
+            .bind(format!("%{}%", name))
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(row.0)
+    }
 
-    // Helper: get colors/arrays, falling back to front face for multi-face cards
-    let get_colors = |field: &str| -> Option<String> {
-        json_array_to_string(&card_json[field])
-            .or_else(|| front_face.and_then(|f| json_array_to_string(&f[field])))
-    };
+    /// One page of Commander-legal cards whose color identity fits within `identity` (color
+    /// letters, e.g. `["R", "G"]` for a Gruul commander; empty for colorless), ordered by name.
+    pub async fn search_in_color_identity(
+        &self,
+        identity: &[String],
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<serde_json::Value>, sqlx::Error> {
+        let identity: Vec<String> = identity.iter().map(|c| c.to_uppercase()).collect();
 
-    // For image_uris: use top-level if present, else front face (DFCs have per-face images)
-    let image_uris_str = if card_json["image_uris"].is_object() {
-        card_json["image_uris"].to_string()
-    } else if let Some(face) = front_face {
-        if face["image_uris"].is_object() {
-            face["image_uris"].to_string()
-        } else {
-            "null".to_string()
+        let rows: Vec<(serde_json::Value,)> = sqlx::query_as(
+            r#"
+            SELECT raw_json FROM cards
+            WHERE raw_json -> 'color_identity' <@ $1
+              AND raw_json -> 'legalities' ->> 'commander' = 'legal'
+            ORDER BY name, id
+            LIMIT $2 OFFSET $3
+            "#,
+        )
+        .bind(serde_json::json!(identity))
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|(json,)| json).collect())
+    }
+
+    /// One page of cards whose colors (color letters, e.g. `["W", "U"]`) relate to `colors`
+    /// as `mode` says, ordered by name. Multi-face cards, which list colors per face, are
+    /// compared by their front face, as in the `colors` column.
+    pub async fn search_by_colors(
+        &self,
+        colors: &[String],
+        mode: ColorMatch,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<serde_json::Value>, sqlx::Error> {
+        let colors: Vec<String> = colors.iter().map(|c| c.to_uppercase()).collect();
+        let condition = match mode {
+            ColorMatch::Exact => "card_colors @> $1 AND card_colors <@ $1",
+            ColorMatch::AtLeast => "card_colors @> $1",
+            ColorMatch::AtMost => "card_colors <@ $1",
+        };
+
+        let rows: Vec<(serde_json::Value,)> = sqlx::query_as(&format!(
+            r#"
+            SELECT raw_json
+            FROM cards,
+                COALESCE(raw_json -> 'colors', raw_json -> 'card_faces' -> 0 -> 'colors')
+                    AS card_colors
+            WHERE {}
+            ORDER BY name, id
+            LIMIT $2 OFFSET $3
+            "#,
+            condition
+        ))
+        .bind(serde_json::json!(colors))
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|(json,)| json).collect())
+    }
+
+    /// All cards in a set, by set code (e.g. "neo"). Served by the `set_code` index.
+    pub async fn search_by_set(
+        &self,
+        set_code: &str,
+    ) -> Result<Vec<serde_json::Value>, sqlx::Error> {
+        let rows: Vec<(serde_json::Value,)> =
+            sqlx::query_as("SELECT raw_json FROM cards WHERE set_code = $1 ORDER BY name")
+                .bind(set_code.to_lowercase())
+                .fetch_all(&self.pool)
+                .await?;
+
+        Ok(rows.into_iter().map(|(json,)| json).collect())
+    }
+
+    /// All cards of a rarity (`common`, `uncommon`, `rare`, `mythic`, ...). Served by the
+    /// `rarity` index.
+    pub async fn search_by_rarity(
+        &self,
+        rarity: &str,
+    ) -> Result<Vec<serde_json::Value>, sqlx::Error> {
+        let rows: Vec<(serde_json::Value,)> =
+            sqlx::query_as("SELECT raw_json FROM cards WHERE rarity = $1 ORDER BY name")
+                .bind(rarity.to_lowercase())
+                .fetch_all(&self.pool)
+                .await?;
+
+        Ok(rows.into_iter().map(|(json,)| json).collect())
+    }
+
+    /// Cards of one rarity within a set, e.g. the mythics of "neo"
+    pub async fn search_by_set_and_rarity(
+        &self,
+        set_code: &str,
+        rarity: &str,
+    ) -> Result<Vec<serde_json::Value>, sqlx::Error> {
+        let rows: Vec<(serde_json::Value,)> = sqlx::query_as(
+            "SELECT raw_json FROM cards WHERE set_code = $1 AND rarity = $2 ORDER BY name",
+        )
+        .bind(set_code.to_lowercase())
+        .bind(rarity.to_lowercase())
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|(json,)| json).collect())
+    }
+
+    /// Cards with a keyword ability, e.g. "Flying" or "flash". Matches whole keywords from
+    /// the comma-separated `keywords` column, ignoring case.
+    pub async fn search_by_keyword(
+        &self,
+        keyword: &str,
+    ) -> Result<Vec<serde_json::Value>, sqlx::Error> {
+        let rows: Vec<(serde_json::Value,)> = sqlx::query_as(
+            "SELECT raw_json FROM cards \
+             WHERE lower($1) = ANY(string_to_array(lower(keywords), ',')) ORDER BY name",
+        )
+        .bind(keyword)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|(json,)| json).collect())
+    }
+
+    /// Every set code in the database, sorted, e.g. to fill a filter menu
+    pub async fn distinct_sets(&self) -> Result<Vec<String>, sqlx::Error> {
+        self.distinct_values(
+            "SELECT DISTINCT set_code FROM cards WHERE set_code IS NOT NULL ORDER BY 1",
+        )
+        .await
+    }
+
+    /// Every rarity in the database, sorted
+    pub async fn distinct_rarities(&self) -> Result<Vec<String>, sqlx::Error> {
+        self.distinct_values(
+            "SELECT DISTINCT rarity FROM cards WHERE rarity IS NOT NULL ORDER BY 1",
+        )
+        .await
+    }
+
+    /// Every keyword ability in the database, sorted, split out of the `keywords` column
+    pub async fn distinct_keywords(&self) -> Result<Vec<String>, sqlx::Error> {
+        self.distinct_values(
+            "SELECT DISTINCT keyword FROM cards, unnest(string_to_array(keywords, ',')) AS keyword \
+             WHERE keyword <> '' ORDER BY 1",
+        )
+        .await
+    }
+
+    /// Run a query returning one text column
+    async fn distinct_values(&self, sql: &str) -> Result<Vec<String>, sqlx::Error> {
+        let rows: Vec<(String,)> = sqlx::query_as(sql).fetch_all(&self.pool).await?;
+        Ok(rows.into_iter().map(|(value,)| value).collect())
+    }
+
+    /// Full-text search over card names and oracle text, best matches first. `query` is plain
+    /// text (e.g. "create a token"); every word must match, with English stemming.
+    pub async fn search_text(&self, query: &str) -> Result<Vec<serde_json::Value>, sqlx::Error> {
+        let rows: Vec<(serde_json::Value,)> = sqlx::query_as(
+            r#"
+            SELECT raw_json
+            FROM cards, plainto_tsquery('english', $1) AS query
+            WHERE search_vector @@ query
+            ORDER BY ts_rank(search_vector, query) DESC, name
+            "#,
+        )
+        .bind(query)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|(json,)| json).collect())
+    }
+
+    /// Cards whose `field` price is at most `max`, cheapest first. `field` is one of the
+    /// Scryfall price keys `usd`, `usd_foil`, `eur` or `tix`; cards without that price are
+    /// never returned.
+    pub async fn search_by_price(
+        &self,
+        field: &str,
+        max: f64,
+    ) -> Result<Vec<serde_json::Value>, sqlx::Error> {
+        let column = format!("price_{}", field);
+        if !PRICE_COLUMNS.contains(&column.as_str()) {
+            return Err(sqlx::Error::ColumnNotFound(column));
         }
-    } else {
-        "null".to_string()
-    };
 
+        let rows: Vec<(serde_json::Value,)> = sqlx::query_as(&format!(
+            "SELECT raw_json FROM cards WHERE {0} <= $1 ORDER BY {0}, name",
+            column
+        ))
+        .bind(max)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|(json,)| json).collect())
+    }
+
+    /// Search cards by a value inside the raw Scryfall JSON, e.g.
+    /// `search_json("legalities.commander", "legal")`. `json_path` is a dot-separated key path;
+    /// the match uses JSONB containment (`@>`), so it is served by the GIN index on `raw_json`.
+    pub async fn search_json(
+        &self,
+        json_path: &str,
+        value: &str,
+    ) -> Result<Vec<serde_json::Value>, sqlx::Error> {
+        let containment = json_path.split('.').rev().fold(
+            serde_json::Value::String(value.to_string()),
+            |inner, key| serde_json::json!({ key: inner }),
+        );
+
+        let rows: Vec<(serde_json::Value,)> =
+            sqlx::query_as("SELECT raw_json FROM cards WHERE raw_json @> $1")
+                .bind(containment)
+                .fetch_all(&self.pool)
+                .await?;
+
+        Ok(rows.into_iter().map(|(json,)| json).collect())
+    }
+}
+
+/// Upsert a `db_metadata` key against any Postgres connection (pool connection or transaction)
+async fn execute_metadata_upsert(
+    conn: &mut PgConnection,
+    key: &str,
+    value: &str,
+) -> Result<(), sqlx::Error> {
     sqlx::query(
+        r#"
+        INSERT INTO db_metadata (key, value, updated_at)
+        VALUES ($1, $2, CURRENT_TIMESTAMP)
+        ON CONFLICT(key) DO UPDATE SET
+            value = EXCLUDED.value,
+            updated_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(key)
+    .bind(value)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Execute a card upsert against any Postgres connection (pool connection or transaction)
+async fn execute_card_upsert(
+    conn: &mut PgConnection,
+    card_json: &serde_json::Value,
+) -> Result<(), sqlx::Error> {
+    card_id(card_json)?;
+
+    let mut query = sqlx::query(
         r#"
         INSERT INTO cards (
             id, oracle_id, name, lang, released_at, uri, scryfall_uri, layout,
@@ -249,13 +898,15 @@ async fn execute_card_upsert(
             illustration_id, border_color, frame, full_art, textless, booster,
             story_spotlight, edhrec_rank, penny_rank, prices, related_uris,
             purchase_uris, image_uris, card_faces, all_parts,
-            loyalty, defense, raw_json, updated_at
+            loyalty, defense, raw_json,
+            price_usd, price_usd_foil, price_eur, price_tix, updated_at
         ) VALUES (
             $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15,
             $16, $17, $18, $19, $20, $21, $22, $23, $24, $25, $26, $27, $28,
             $29, $30, $31, $32, $33, $34, $35, $36, $37, $38, $39, $40, $41,
             $42, $43, $44, $45, $46, $47, $48, $49, $50, $51, $52, $53, $54,
-            $55, $56, $57, $58, $59, $60, $61, $62, $63, CURRENT_TIMESTAMP
+            $55, $56, $57, $58, $59, $60, $61, $62, $63, $64, $65, $66, $67,
+            CURRENT_TIMESTAMP
         )
         ON CONFLICT(id) DO UPDATE SET
             oracle_id = EXCLUDED.oracle_id,
@@ -320,78 +971,233 @@ async fn execute_card_upsert(
             loyalty = EXCLUDED.loyalty,
             defense = EXCLUDED.defense,
             raw_json = EXCLUDED.raw_json,
+            price_usd = EXCLUDED.price_usd,
+            price_usd_foil = EXCLUDED.price_usd_foil,
+            price_eur = EXCLUDED.price_eur,
+            price_tix = EXCLUDED.price_tix,
             updated_at = CURRENT_TIMESTAMP
         "#,
-    )
-    .bind(id)                                                          // $1
-    .bind(card_json["oracle_id"].as_str())                             // $2
-    .bind(card_json["name"].as_str())                                  // $3
-    .bind(card_json["lang"].as_str())                                  // $4
-    .bind(card_json["released_at"].as_str())                           // $5
-    .bind(card_json["uri"].as_str())                                   // $6
-    .bind(card_json["scryfall_uri"].as_str())                          // $7
-    .bind(card_json["layout"].as_str())                                // $8
-    .bind(card_json["highres_image"].as_bool())                        // $9
-    .bind(card_json["image_status"].as_str())                          // $10
-    .bind(get_str("mana_cost"))                                        // $11
-    .bind(card_json["cmc"].as_f64())                                   // $12
-    .bind(card_json["type_line"].as_str())                             // $13
-    .bind(get_str("oracle_text"))                                      // $14
-    .bind(get_str("power"))                                            // $15
-    .bind(get_str("toughness"))                                        // $16
-    .bind(get_colors("colors"))                                        // $17
-    .bind(get_colors("color_identity"))                                // $18
-    .bind(json_array_to_string(&card_json["keywords"]))                // $19
-    .bind(card_json["legalities"].to_string())                         // $20
-    .bind(json_array_to_string(&card_json["games"]))                   // $21
-    .bind(card_json["reserved"].as_bool())                             // $22
-    .bind(card_json["foil"].as_bool())                                 // $23
-    .bind(card_json["nonfoil"].as_bool())                              // $24
-    .bind(json_array_to_string(&card_json["finishes"]))                // $25
-    .bind(card_json["oversized"].as_bool())                            // $26
-    .bind(card_json["promo"].as_bool())                                // $27
-    .bind(card_json["reprint"].as_bool())                              // $28
-    .bind(card_json["variation"].as_bool())                            // $29
-    .bind(card_json["set_id"].as_str())                                // $30
-    .bind(card_json["set"].as_str())                                   // $31
-    .bind(card_json["set_name"].as_str())                              // $32
-    .bind(card_json["set_type"].as_str())                              // $33
-    .bind(card_json["set_uri"].as_str())                               // $34
-    .bind(card_json["set_search_uri"].as_str())                        // $35
-    .bind(card_json["scryfall_set_uri"].as_str())                      // $36
-    .bind(card_json["rulings_uri"].as_str())                           // $37
-    .bind(card_json["prints_search_uri"].as_str())                     // $38
-    .bind(card_json["collector_number"].as_str())                      // $39
-    .bind(card_json["digital"].as_bool())                              // $40
-    .bind(card_json["rarity"].as_str())                                // $41
-    .bind(get_str("flavor_text"))                                      // $42 (DFC fallback)
-    .bind(card_json["card_back_id"].as_str())                          // $43
-    .bind(get_str("artist"))                                           // $44 (DFC fallback)
-    .bind(json_array_to_string(&card_json["artist_ids"]))              // $45
-    .bind(get_str("illustration_id"))                                  // $46 (DFC fallback)
-    .bind(card_json["border_color"].as_str())                          // $47
-    .bind(card_json["frame"].as_str())                                 // $48
-    .bind(card_json["full_art"].as_bool())                             // $49
-    .bind(card_json["textless"].as_bool())                             // $50
-    .bind(card_json["booster"].as_bool())                              // $51
-    .bind(card_json["story_spotlight"].as_bool())                      // $52
-    .bind(card_json["edhrec_rank"].as_i64().map(|n| n as i32))         // $53
-    .bind(card_json["penny_rank"].as_i64().map(|n| n as i32))          // $54
-    .bind(card_json["prices"].to_string())                             // $55
-    .bind(card_json["related_uris"].to_string())                       // $56
-    .bind(card_json["purchase_uris"].to_string())                      // $57
-    .bind(&image_uris_str)                                             // $58
-    .bind(card_json["card_faces"].to_string())                         // $59
-    .bind(card_json["all_parts"].to_string())                          // $60
-    .bind(get_str("loyalty"))                                          // $61 (DFC fallback)
-    .bind(get_str("defense"))                                          // $62 (DFC fallback)
-    .bind(&raw_json)                                                   // $63
-    .execute(&mut *conn)
-    .await?;
+    );
+    for column in card_columns(card_json) {
+        query = column.bind(query);
+    }
+    query.execute(&mut *conn).await?;
 
     Ok(())
 }
 
+/// The card's Scryfall id, which is the `cards` primary key. A missing or empty id is an error:
+/// storing it as `''` would make every id-less card overwrite the previous one.
+fn card_id(card_json: &serde_json::Value) -> Result<&str, sqlx::Error> {
+    match card_json["id"].as_str() {
+        Some(id) if !id.is_empty() => Ok(id),
+        _ => {
+            let name = card_json["name"].as_str().unwrap_or("<unnamed>");
+            Err(sqlx::Error::InvalidArgument(format!(
+                "card '{}' has no id",
+                name
+            )))
+        }
+    }
+}
+
+/// Extract the `cards` column values from a Scryfall card object, in `CARD_COLUMNS` order.
+/// Handles all card layouts: normal, transform, modal_dfc, split, flip, adventure, meld, etc.
+fn card_columns(card_json: &serde_json::Value) -> Vec<CardColumn<'_>> {
+    let id = card_json["id"].as_str().unwrap_or_default();
+
+    // For double-faced/split/adventure/flip cards, Scryfall puts per-face fields
+    // (mana_cost, oracle_text, power, toughness, loyalty, flavor_text, etc.)
+    // in card_faces[] instead of at the top level.
+    let front_face = card_json["card_faces"].as_array().and_then(|f| f.first());
+
+    // Helper: get a string field, falling back to front face for multi-face cards
+    let get_str = |field: &str| -> Option<&str> {
+        card_json[field]
+            .as_str()
+            .or_else(|| front_face.and_then(|f| f[field].as_str()))
+    };
+
+    // Helper: get colors/arrays, falling back to front face for multi-face cards
+    let get_colors = |field: &str| -> Option<String> {
+        json_array_to_string(&card_json[field])
+            .or_else(|| front_face.and_then(|f| json_array_to_string(&f[field])))
+    };
+
+    // For image_uris: use top-level if present, else front face (DFCs have per-face images)
+    let image_uris_str = if card_json["image_uris"].is_object() {
+        card_json["image_uris"].to_string()
+    } else if let Some(face) = front_face {
+        if face["image_uris"].is_object() {
+            face["image_uris"].to_string()
+        } else {
+            "null".to_string()
+        }
+    } else {
+        "null".to_string()
+    };
+
+    // Scryfall sends prices as decimal strings, or null when there is no market price
+    let get_price = |field: &str| -> Option<f64> {
+        card_json["prices"][field]
+            .as_str()
+            .and_then(|price| price.parse().ok())
+    };
+
+    vec![
+        CardColumn::Text(Some(id)),                                          // $1
+        CardColumn::Text(card_json["oracle_id"].as_str()),                   // $2
+        CardColumn::Text(card_json["name"].as_str()),                        // $3
+        CardColumn::Text(card_json["lang"].as_str()),                        // $4
+        CardColumn::Text(card_json["released_at"].as_str()),                 // $5
+        CardColumn::Text(card_json["uri"].as_str()),                         // $6
+        CardColumn::Text(card_json["scryfall_uri"].as_str()),                // $7
+        CardColumn::Text(card_json["layout"].as_str()),                      // $8
+        CardColumn::Bool(card_json["highres_image"].as_bool()),              // $9
+        CardColumn::Text(card_json["image_status"].as_str()),                // $10
+        CardColumn::Text(get_str("mana_cost")),                              // $11
+        CardColumn::Float(card_json["cmc"].as_f64()),                        // $12
+        CardColumn::Text(card_json["type_line"].as_str()),                   // $13
+        CardColumn::Text(get_str("oracle_text")),                            // $14
+        CardColumn::Text(get_str("power")),                                  // $15
+        CardColumn::Text(get_str("toughness")),                              // $16
+        CardColumn::OwnedText(get_colors("colors")),                         // $17
+        CardColumn::OwnedText(get_colors("color_identity")),                 // $18
+        CardColumn::OwnedText(json_array_to_string(&card_json["keywords"])), // $19
+        CardColumn::OwnedText(Some(card_json["legalities"].to_string())),    // $20
+        CardColumn::OwnedText(json_array_to_string(&card_json["games"])),    // $21
+        CardColumn::Bool(card_json["reserved"].as_bool()),                   // $22
+        CardColumn::Bool(card_json["foil"].as_bool()),                       // $23
+        CardColumn::Bool(card_json["nonfoil"].as_bool()),                    // $24
+        CardColumn::OwnedText(json_array_to_string(&card_json["finishes"])), // $25
+        CardColumn::Bool(card_json["oversized"].as_bool()),                  // $26
+        CardColumn::Bool(card_json["promo"].as_bool()),                      // $27
+        CardColumn::Bool(card_json["reprint"].as_bool()),                    // $28
+        CardColumn::Bool(card_json["variation"].as_bool()),                  // $29
+        CardColumn::Text(card_json["set_id"].as_str()),                      // $30
+        CardColumn::Text(card_json["set"].as_str()),                         // $31
+        CardColumn::Text(card_json["set_name"].as_str()),                    // $32
+        CardColumn::Text(card_json["set_type"].as_str()),                    // $33
+        CardColumn::Text(card_json["set_uri"].as_str()),                     // $34
+        CardColumn::Text(card_json["set_search_uri"].as_str()),              // $35
+        CardColumn::Text(card_json["scryfall_set_uri"].as_str()),            // $36
+        CardColumn::Text(card_json["rulings_uri"].as_str()),                 // $37
+        CardColumn::Text(card_json["prints_search_uri"].as_str()),           // $38
+        CardColumn::Text(card_json["collector_number"].as_str()),            // $39
+        CardColumn::Bool(card_json["digital"].as_bool()),                    // $40
+        CardColumn::Text(card_json["rarity"].as_str()),                      // $41
+        CardColumn::Text(get_str("flavor_text")),                            // $42 (DFC fallback)
+        CardColumn::Text(card_json["card_back_id"].as_str()),                // $43
+        CardColumn::Text(get_str("artist")),                                 // $44 (DFC fallback)
+        CardColumn::OwnedText(json_array_to_string(&card_json["artist_ids"])), // $45
+        CardColumn::Text(get_str("illustration_id")),                        // $46 (DFC fallback)
+        CardColumn::Text(card_json["border_color"].as_str()),                // $47
+        CardColumn::Text(card_json["frame"].as_str()),                       // $48
+        CardColumn::Bool(card_json["full_art"].as_bool()),                   // $49
+        CardColumn::Bool(card_json["textless"].as_bool()),                   // $50
+        CardColumn::Bool(card_json["booster"].as_bool()),                    // $51
+        CardColumn::Bool(card_json["story_spotlight"].as_bool()),            // $52
+        CardColumn::Int(card_json["edhrec_rank"].as_i64().map(|n| n as i32)), // $53
+        CardColumn::Int(card_json["penny_rank"].as_i64().map(|n| n as i32)), // $54
+        CardColumn::OwnedText(Some(card_json["prices"].to_string())),        // $55
+        CardColumn::OwnedText(Some(card_json["related_uris"].to_string())),  // $56
+        CardColumn::OwnedText(Some(card_json["purchase_uris"].to_string())), // $57
+        CardColumn::OwnedText(Some(image_uris_str)),                         // $58
+        CardColumn::OwnedText(Some(card_json["card_faces"].to_string())),    // $59
+        CardColumn::OwnedText(Some(card_json["all_parts"].to_string())),     // $60
+        CardColumn::Text(get_str("loyalty")),                                // $61 (DFC fallback)
+        CardColumn::Text(get_str("defense")),                                // $62 (DFC fallback)
+        CardColumn::Json(card_json),                                         // $63
+        CardColumn::Float(get_price("usd")),                                 // $64
+        CardColumn::Float(get_price("usd_foil")),                            // $65
+        CardColumn::Float(get_price("eur")),                                 // $66
+        CardColumn::Float(get_price("tix")),                                 // $67
+    ]
+}
+
+/// `cards` columns in the order `card_columns` produces them (everything but the timestamps and
+/// the generated `search_vector`)
+const CARD_COLUMNS: &str = "id, oracle_id, name, lang, released_at, uri, scryfall_uri, layout, \
+    highres_image, image_status, mana_cost, cmc, type_line, oracle_text, power, toughness, \
+    colors, color_identity, keywords, legalities, games, reserved, foil, nonfoil, finishes, \
+    oversized, promo, reprint, variation, set_id, set_code, set_name, set_type, set_uri, \
+    set_search_uri, scryfall_set_uri, rulings_uri, prints_search_uri, collector_number, \
+    digital, rarity, flavor_text, card_back_id, artist, artist_ids, illustration_id, \
+    border_color, frame, full_art, textless, booster, story_spotlight, edhrec_rank, penny_rank, \
+    prices, related_uris, purchase_uris, image_uris, card_faces, all_parts, loyalty, defense, \
+    raw_json, price_usd, price_usd_foil, price_eur, price_tix";
+
+/// Typed price columns, one per `prices` key they are parsed from (`price_usd` <- `usd`)
+const PRICE_COLUMNS: [&str; 4] = ["price_usd", "price_usd_foil", "price_eur", "price_tix"];
+
+/// One `cards` column value, shared by the upsert (bound as a parameter) and `COPY` (written
+/// as a CSV field) paths so both store exactly the same row
+enum CardColumn<'a> {
+    Text(Option<&'a str>),
+    OwnedText(Option<String>),
+    Bool(Option<bool>),
+    Float(Option<f64>),
+    Int(Option<i32>),
+    Json(&'a serde_json::Value),
+}
+
+impl<'a> CardColumn<'a> {
+    fn bind<'q>(self, query: Query<'q, Postgres, PgArguments>) -> Query<'q, Postgres, PgArguments>
+    where
+        'a: 'q,
+    {
+        match self {
+            CardColumn::Text(value) => query.bind(value),
+            CardColumn::OwnedText(value) => query.bind(value),
+            CardColumn::Bool(value) => query.bind(value),
+            CardColumn::Float(value) => query.bind(value),
+            CardColumn::Int(value) => query.bind(value),
+            CardColumn::Json(value) => query.bind(value),
+        }
+    }
+
+    /// Append the value as a CSV field; NULL is an empty unquoted field
+    fn write_csv(&self, out: &mut String) {
+        match self {
+            CardColumn::Text(Some(value)) => write_csv_text(out, value),
+            CardColumn::OwnedText(Some(value)) => write_csv_text(out, value),
+            CardColumn::Bool(Some(value)) => out.push(if *value { 't' } else { 'f' }),
+            CardColumn::Float(Some(value)) => out.push_str(&value.to_string()),
+            CardColumn::Int(Some(value)) => out.push_str(&value.to_string()),
+            CardColumn::Json(value) => write_csv_text(out, &value.to_string()),
+            CardColumn::Text(None)
+            | CardColumn::OwnedText(None)
+            | CardColumn::Bool(None)
+            | CardColumn::Float(None)
+            | CardColumn::Int(None) => {}
+        }
+    }
+}
+
+/// Append a quoted CSV field, doubling embedded quotes. Quoting also keeps empty strings
+/// distinct from NULL.
+fn write_csv_text(out: &mut String, value: &str) {
+    out.push('"');
+    for c in value.chars() {
+        if c == '"' {
+            out.push('"');
+        }
+        out.push(c);
+    }
+    out.push('"');
+}
+
+/// Append one card as a CSV line in `CARD_COLUMNS` order
+fn write_card_csv(out: &mut String, card_json: &serde_json::Value) {
+    for (i, column) in card_columns(card_json).iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        column.write_csv(out);
+    }
+    out.push('\n');
+}
+
 /// Helper function to convert JSON arrays to comma-separated strings
 fn json_array_to_string(value: &serde_json::Value) -> Option<String> {
     value.as_array().map(|arr| {
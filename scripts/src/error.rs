@@ -12,6 +12,8 @@ pub enum QueryValidationError {
     ConsecutiveOperators,
     TrailingOperator,
     LeadingOperator,
+    /// `or`/`and` written without spaces around it; holds the term it is in
+    GluedOperator(String),
 }
 
 impl std::fmt::Display for QueryValidationError {
@@ -36,6 +38,9 @@ impl std::fmt::Display for QueryValidationError {
             QueryValidationError::LeadingOperator => {
                 write!(f, "Query cannot start with an operator")
             }
+            QueryValidationError::GluedOperator(near) => {
+                write!(f, "Operator needs spaces around it: '{}'", near)
+            }
         }
     }
 }
@@ -48,6 +53,15 @@ pub enum ScryfallError {
     ValidationError(QueryValidationError),
     RequestError(Arc<reqwest::Error>),
     DatabaseError(String),
+    NotFound(String),
+    /// Non-success HTTP status, with the `details` and `warnings` from Scryfall's error body
+    ApiError {
+        status: u16,
+        details: String,
+        warnings: Vec<String>,
+    },
+    /// A client setting could not be applied (e.g. a user agent that is not a valid header)
+    InvalidConfig(String),
 }
 
 impl std::fmt::Display for ScryfallError {
@@ -56,6 +70,11 @@ impl std::fmt::Display for ScryfallError {
             ScryfallError::ValidationError(e) => write!(f, "Query validation failed: {}", e),
             ScryfallError::RequestError(e) => write!(f, "Request failed: {}", e),
             ScryfallError::DatabaseError(e) => write!(f, "Database error: {}", e),
+            ScryfallError::NotFound(what) => write!(f, "Not found: {}", what),
+            ScryfallError::ApiError {
+                status, details, ..
+            } => write!(f, "Scryfall API error ({}): {}", status, details),
+            ScryfallError::InvalidConfig(e) => write!(f, "Invalid client configuration: {}", e),
         }
     }
 }
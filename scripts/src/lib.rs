@@ -2,11 +2,20 @@ pub mod client;
 pub mod database;
 pub mod error;
 pub mod models;
+pub mod progress;
 pub mod rate_limiter;
 pub mod validator;
 
-pub use client::ScryfallClient;
-pub use database::Database;
+pub use client::{
+    BulkCacheState, BulkImport, BulkImportOptions, CacheValidators, DedupedCards, FetchLimits,
+    FetchOutcome, QueryPlan, ScryfallClient, ScryfallClientBuilder, SearchPage, StoreSummary,
+};
+pub use database::{ColorMatch, Database};
 pub use error::{QueryValidationError, ScryfallError};
-pub use models::{Card, ScryfallSearchResponse};
-pub use validator::QueryValidator;
+pub use models::{
+    Card, CardCollection, CardFace, CardIdentifier, ImageUris, Prices, ScryfallSearchResponse,
+    cards_to_csv,
+};
+pub use progress::Progress;
+pub use rate_limiter::{RateLimiter, RateLimiterStats};
+pub use validator::{Lint, LintSeverity, QueryValidator};
@@ -1,23 +1,53 @@
 use std::time::Instant;
 
-use scripts::{Database, ScryfallClient};
+use scripts::{BulkImport, BulkImportOptions, Database, Progress, ScryfallClient};
+use tokio_util::sync::CancellationToken;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Load environment variables from .env file
     dotenvy::dotenv().ok();
 
-    let client = ScryfallClient::new();
+    // Stop cleanly between batches on Ctrl-C, keeping everything stored so far
+    let cancel = CancellationToken::new();
+    let ctrl_c_token = cancel.clone();
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            ctrl_c_token.cancel();
+        }
+    });
+
+    let client = ScryfallClient::from_env()?.with_cancellation_token(cancel);
     let db = Database::new().await?;
     let start = Instant::now();
 
     println!("=== Downloading and storing all cards from Scryfall bulk data ===\n");
 
-    match client.download_and_store_bulk(&db).await {
-        Ok(total_stored) => {
+    // `--prune` deletes cards that are no longer part of the Scryfall snapshot
+    let options = BulkImportOptions {
+        prune_missing: std::env::args().any(|arg| arg == "--prune"),
+        ..BulkImportOptions::default()
+    };
+
+    match client
+        .download_and_store_bulk_with(&db, &options, Progress::print)
+        .await
+    {
+        Ok(BulkImport::UpToDate) => {
+            println!("\nNothing to import, card data is unchanged.");
+            println!("Total cards in database: {}", db.get_card_count().await?);
+        }
+        Ok(BulkImport::Cancelled(stored)) => {
+            println!("\nImport cancelled after storing {} cards.", stored);
+            println!("Total cards in database: {}", db.get_card_count().await?);
+        }
+        Ok(BulkImport::Stored(total_stored)) => {
             println!("\n=== Results ===");
             println!("Total cards stored: {}", total_stored);
             println!("Total cards in database: {}", db.get_card_count().await?);
+            if let Some(version) = db.get_bulk_version().await? {
+                println!("Card data as of: {}", version);
+            }
             println!("Total time: {:.2}s", start.elapsed().as_secs_f64());
         }
         Err(e) => {
@@ -1,4 +1,5 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 #[allow(dead_code)]
 #[derive(Deserialize, Debug)]
@@ -8,16 +9,178 @@ pub struct ScryfallSearchResponse {
     pub has_more: bool,
     pub next_page: Option<String>,
     pub data: Vec<Card>,
+    /// Non-fatal problems with the query, e.g. a clause Scryfall ignored
+    #[serde(default)]
+    pub warnings: Vec<String>,
 }
 
+/// A card as returned by the Scryfall API. Deserialization is lenient: anything Scryfall
+/// leaves out (e.g. `cmc` on some tokens, `image_uris` on double-faced cards) becomes `None` or
+/// empty instead of failing the whole page.
 #[allow(dead_code)]
 #[derive(Deserialize, Debug, Clone)]
 pub struct Card {
     pub id: String,
     pub name: String,
     pub mana_cost: Option<String>,
+    pub cmc: Option<f64>,
     pub type_line: Option<String>,
     pub oracle_text: Option<String>,
+    pub power: Option<String>,
+    pub toughness: Option<String>,
+    pub loyalty: Option<String>,
+    /// Missing on multi-face cards, where each face has its own colors
+    pub colors: Option<Vec<String>>,
+    #[serde(default)]
+    pub color_identity: Vec<String>,
+    /// Set code, e.g. `"lea"`
+    #[serde(default)]
+    pub set: String,
+    #[serde(default)]
     pub set_name: String,
+    #[serde(default)]
+    pub collector_number: String,
+    #[serde(default)]
     pub rarity: String,
+    /// Missing on multi-face cards, where each face has its own images
+    pub image_uris: Option<ImageUris>,
+    /// Format name to legality (`"legal"`, `"not_legal"`, `"restricted"`, `"banned"`)
+    #[serde(default)]
+    pub legalities: HashMap<String, String>,
+    #[serde(default)]
+    pub prices: Prices,
+    /// Faces of transform, modal DFC, split, flip and adventure cards, front face first
+    pub card_faces: Option<Vec<CardFace>>,
+}
+
+impl Card {
+    /// The face shown on the front of the card. Top-level fields win; for multi-face cards,
+    /// fields Scryfall only sets per face (mana cost, rules text, stats, images) come from the
+    /// first face, and the name is the first face's own name.
+    pub fn front(&self) -> CardFace {
+        let first = self.card_faces.as_deref().and_then(|faces| faces.first());
+        let or_face = |top: &Option<String>, field: fn(&CardFace) -> &Option<String>| {
+            top.clone()
+                .or_else(|| first.and_then(|face| field(face).clone()))
+        };
+
+        CardFace {
+            name: first.map_or_else(|| self.name.clone(), |face| face.name.clone()),
+            mana_cost: or_face(&self.mana_cost, |face| &face.mana_cost),
+            type_line: or_face(&self.type_line, |face| &face.type_line),
+            oracle_text: or_face(&self.oracle_text, |face| &face.oracle_text),
+            power: or_face(&self.power, |face| &face.power),
+            toughness: or_face(&self.toughness, |face| &face.toughness),
+            loyalty: or_face(&self.loyalty, |face| &face.loyalty),
+            image_uris: self
+                .image_uris
+                .clone()
+                .or_else(|| first.and_then(|face| face.image_uris.clone())),
+        }
+    }
+}
+
+/// Columns written by [`cards_to_csv`]
+const CSV_HEADER: [&str; 7] = [
+    "name",
+    "set_code",
+    "collector_number",
+    "rarity",
+    "cmc",
+    "mana_cost",
+    "type_line",
+];
+
+/// Export cards as CSV for spreadsheets: a header row, then one line per card. Mana cost and
+/// type line come from [`Card::front`], so double-faced cards show their front face's cost.
+/// Fields containing commas, quotes or line breaks are quoted.
+pub fn cards_to_csv(cards: &[Card]) -> String {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    // Writing into a Vec cannot fail
+    writer
+        .write_record(CSV_HEADER)
+        .expect("CSV write to memory");
+
+    for card in cards {
+        let front = card.front();
+        let cmc = card.cmc.map(|cmc| cmc.to_string()).unwrap_or_default();
+        writer
+            .write_record([
+                card.name.as_str(),
+                &card.set,
+                &card.collector_number,
+                &card.rarity,
+                &cmc,
+                front.mana_cost.as_deref().unwrap_or_default(),
+                front.type_line.as_deref().unwrap_or_default(),
+            ])
+            .expect("CSV write to memory");
+    }
+
+    let bytes = writer.into_inner().expect("CSV flush to memory");
+    String::from_utf8(bytes).expect("CSV of UTF-8 fields is UTF-8")
+}
+
+/// One face of a multi-face card
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct CardFace {
+    #[serde(default)]
+    pub name: String,
+    pub mana_cost: Option<String>,
+    pub type_line: Option<String>,
+    pub oracle_text: Option<String>,
+    pub power: Option<String>,
+    pub toughness: Option<String>,
+    pub loyalty: Option<String>,
+    pub image_uris: Option<ImageUris>,
+}
+
+/// Image URLs for a card or card face
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct ImageUris {
+    pub small: Option<String>,
+    pub normal: Option<String>,
+    pub large: Option<String>,
+    pub png: Option<String>,
+    pub art_crop: Option<String>,
+    pub border_crop: Option<String>,
+}
+
+/// Current market prices; Scryfall sends them as decimal strings, `None` when unknown
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct Prices {
+    pub usd: Option<String>,
+    pub usd_foil: Option<String>,
+    pub usd_etched: Option<String>,
+    pub eur: Option<String>,
+    pub eur_foil: Option<String>,
+    pub tix: Option<String>,
+}
+
+/// Card identifier accepted by Scryfall's `/cards/collection` endpoint
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum CardIdentifier {
+    /// Scryfall card id
+    Id { id: String },
+    /// Exact card name (newest printing)
+    Name { name: String },
+    /// Exact card name within a set
+    NameAndSet { name: String, set: String },
+    /// Set code plus collector number
+    SetAndNumber {
+        set: String,
+        collector_number: String,
+    },
+}
+
+/// Aggregated result of a `/cards/collection` lookup
+#[derive(Debug, Clone, Default)]
+pub struct CardCollection {
+    /// Cards that were found, in request order
+    pub data: Vec<serde_json::Value>,
+    /// Identifiers Scryfall could not match, as echoed back by the API
+    pub not_found: Vec<serde_json::Value>,
 }
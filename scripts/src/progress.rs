@@ -0,0 +1,167 @@
+use std::io::Write;
+use std::time::Duration;
+
+/// Progress events emitted by long-running fetch and import operations
+#[derive(Debug, Clone)]
+pub enum Progress {
+    /// The bulk data catalog is being requested
+    FetchingCatalog,
+    /// The bulk data entry to download was found in the catalog
+    BulkDataFound {
+        updated_at: String,
+        download_uri: String,
+    },
+    /// The bulk download response arrived; `total_bytes` is the advertised size, if any
+    DownloadStarted { total_bytes: Option<u64> },
+    /// Bytes received so far for the bulk download
+    Downloading { downloaded: u64, total: Option<u64> },
+    /// The bulk download finished
+    DownloadComplete { bytes: u64, elapsed: Duration },
+    /// The downloaded JSON is being parsed
+    Parsing,
+    /// The downloaded JSON was parsed into individual cards
+    Parsed { cards: usize, elapsed: Duration },
+    /// Cards written to the database so far
+    Storing {
+        stored: usize,
+        total: usize,
+        elapsed: Duration,
+    },
+    /// All cards were written to the database
+    StoreComplete { stored: usize, elapsed: Duration },
+    /// Cards no longer present in the bulk snapshot were deleted
+    Pruned { deleted: u64 },
+    /// Pruning was skipped because the snapshot's `snapshot` ids are far fewer than the
+    /// `card_count` cards already stored
+    PruneSkipped { snapshot: usize, card_count: i64 },
+    /// Table statistics are being refreshed after the import
+    Analyzing,
+    /// The bulk data has not changed since the last import, so nothing was downloaded
+    UpToDate,
+    /// The operation was cancelled after storing `stored` cards
+    Cancelled { stored: usize },
+    /// A search results page is being requested
+    FetchingPage { page: u32 },
+    /// A search results page was fetched; `cards` is how many it held
+    PageFetched {
+        page: u32,
+        cards: usize,
+        total_cards: u64,
+        elapsed: Duration,
+    },
+    /// A search results page was fetched and its cards stored
+    PageStored {
+        page: u32,
+        cards: usize,
+        stored: usize,
+        total_cards: u64,
+        elapsed: Duration,
+    },
+}
+
+impl Progress {
+    /// Prints the event to stdout; this is the default reporter for the CLI
+    pub fn print(self) {
+        match self {
+            Progress::FetchingCatalog => println!("Fetching bulk data catalog..."),
+            Progress::BulkDataFound {
+                updated_at,
+                download_uri,
+            } => {
+                println!("Bulk data last updated: {}", updated_at);
+                println!("Downloading: {}", download_uri);
+            }
+            Progress::DownloadStarted { total_bytes } => {
+                if let Some(total) = total_bytes {
+                    println!("Download size: {:.1} MB", total as f64 / 1_048_576.0);
+                }
+            }
+            Progress::Downloading { downloaded, total } => {
+                if let Some(total) = total {
+                    let pct = (downloaded as f64 / total as f64) * 100.0;
+                    print!(
+                        "\rDownloading: {:.1}/{:.1} MB ({:.1}%)",
+                        downloaded as f64 / 1_048_576.0,
+                        total as f64 / 1_048_576.0,
+                        pct
+                    );
+                } else {
+                    print!("\rDownloading: {:.1} MB", downloaded as f64 / 1_048_576.0);
+                }
+                std::io::stdout().flush().ok();
+            }
+            Progress::DownloadComplete { bytes, elapsed } => println!(
+                "\nDownload complete in {:.1}s ({:.1} MB)",
+                elapsed.as_secs_f64(),
+                bytes as f64 / 1_048_576.0
+            ),
+            Progress::Parsing => println!("Parsing JSON..."),
+            Progress::Parsed { cards, elapsed } => {
+                println!("Parsed {} cards in {:.1}s", cards, elapsed.as_secs_f64())
+            }
+            Progress::Storing {
+                stored,
+                total,
+                elapsed,
+            } => {
+                let secs = elapsed.as_secs_f64();
+                let rate = if secs > 0.0 {
+                    stored as f64 / secs
+                } else {
+                    0.0
+                };
+                print!(
+                    "\rStoring: {}/{} ({:.1}%) - {:.0} cards/sec",
+                    stored,
+                    total,
+                    (stored as f64 / total as f64) * 100.0,
+                    rate
+                );
+                std::io::stdout().flush().ok();
+            }
+            Progress::StoreComplete { stored, elapsed } => {
+                println!("\nStored {} cards in {:.1}s", stored, elapsed.as_secs_f64())
+            }
+            Progress::Pruned { deleted } => {
+                println!("Removed {} cards no longer in the bulk data", deleted)
+            }
+            Progress::PruneSkipped {
+                snapshot,
+                card_count,
+            } => println!(
+                "Skipped pruning: the bulk data has {} cards but the database holds {}",
+                snapshot, card_count
+            ),
+            Progress::Analyzing => println!("Updating table statistics..."),
+            Progress::UpToDate => println!("Bulk data is already up to date"),
+            Progress::Cancelled { stored } => {
+                println!("\nCancelled after storing {} cards", stored)
+            }
+            Progress::FetchingPage { page } => println!("Fetching page {}...", page),
+            Progress::PageFetched {
+                cards,
+                total_cards,
+                elapsed,
+                ..
+            } => println!(
+                "  Got {} cards (total: {}) [{:.2}s elapsed]",
+                cards,
+                total_cards,
+                elapsed.as_secs_f64()
+            ),
+            Progress::PageStored {
+                cards,
+                stored,
+                total_cards,
+                elapsed,
+                ..
+            } => println!(
+                "  Got {} cards, stored {} (total: {}) [{:.2}s elapsed]",
+                cards,
+                stored,
+                total_cards,
+                elapsed.as_secs_f64()
+            ),
+        }
+    }
+}
@@ -1,32 +1,215 @@
+use std::sync::Mutex as StdMutex;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::{Duration, Instant};
 use tokio::sync::{Mutex, Semaphore};
 
-/// Rate limiter that enforces Scryfall's API limits (max 10 req/sec, 100ms between requests)
+/// Rate limiter that enforces Scryfall's API limits (max 10 req/sec, 100ms between requests).
+/// All state is behind async locks and atomics, so one limiter wrapped in an `Arc` can be shared
+/// by any number of clients and tasks; see [`ScryfallClient::with_rate_limiter`].
+///
+/// [`ScryfallClient::with_rate_limiter`]: crate::ScryfallClient::with_rate_limiter
 pub struct RateLimiter {
-    last_request: Mutex<Instant>,
+    pacing: Mutex<Pacing>,
     semaphore: Semaphore,
-    min_delay: Duration,
+    configured_delay: Duration,
+    /// No request is let through before this, after a 429 or server error; see `penalize`
+    penalty_until: StdMutex<Option<Instant>>,
+    acquisitions: AtomicU64,
+    throttled: AtomicU64,
+    sleep_nanos: AtomicU64,
+    penalties: AtomicU64,
+    penalty_nanos: AtomicU64,
+}
+
+/// How requests are spaced out
+enum Pacing {
+    /// At least `min_delay` between any two requests
+    FixedDelay {
+        last_request: Instant,
+        min_delay: Duration,
+    },
+    /// Each request takes a token; tokens refill continuously up to `capacity`, so an idle
+    /// limiter allows a burst of `capacity` requests
+    TokenBucket {
+        tokens: f64,
+        capacity: f64,
+        refill_per_sec: f64,
+        last_refill: Instant,
+    },
+}
+
+impl Pacing {
+    /// How long the next request has to wait, updating the state as if it has been let through
+    fn reserve(&mut self) -> Duration {
+        match self {
+            Pacing::FixedDelay {
+                last_request,
+                min_delay,
+            } => {
+                let elapsed = last_request.elapsed();
+                let wait = min_delay.saturating_sub(elapsed);
+                *last_request = Instant::now() + wait;
+                wait
+            }
+            Pacing::TokenBucket {
+                tokens,
+                capacity,
+                refill_per_sec,
+                last_refill,
+            } => {
+                let now = Instant::now();
+                *tokens = (*tokens
+                    + now.duration_since(*last_refill).as_secs_f64() * *refill_per_sec)
+                    .min(*capacity);
+                *last_refill = now;
+
+                // Going negative books the token that refills during the wait
+                *tokens -= 1.0;
+                if *tokens >= 0.0 {
+                    Duration::ZERO
+                } else {
+                    Duration::from_secs_f64(-*tokens / *refill_per_sec)
+                }
+            }
+        }
+    }
+}
+
+/// Snapshot of a [`RateLimiter`]'s counters since it was created
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimiterStats {
+    /// Completed `acquire` calls, i.e. requests let through
+    pub acquisitions: u64,
+    /// How many of those had to sleep before being let through
+    pub throttled: u64,
+    /// Total time spent sleeping across all `acquire` calls, penalty waits included
+    pub total_sleep: Duration,
+    /// `penalize` calls, i.e. responses that told the client to back off
+    pub penalties: u64,
+    /// The part of `total_sleep` spent waiting out penalties
+    pub total_penalty: Duration,
+    /// Time left in the current penalty window; zero when requests are not being held back
+    pub penalty_remaining: Duration,
+    /// Spacing the limiter was built with: the minimum delay for a fixed-delay limiter, the
+    /// average spacing at the refill rate for a token bucket. Penalties come on top of it.
+    pub configured_delay: Duration,
 }
 
 impl RateLimiter {
     pub fn new(max_concurrent: usize, min_delay_ms: u64) -> Self {
+        Self::with_pacing(
+            max_concurrent,
+            Pacing::FixedDelay {
+                last_request: Instant::now() - Duration::from_secs(1),
+                min_delay: Duration::from_millis(min_delay_ms),
+            },
+        )
+    }
+
+    /// Token-bucket limiter: up to `capacity` requests may go out back to back after a quiet
+    /// period, while the long-run rate stays at `refill_per_sec`. Starts full.
+    pub fn token_bucket(capacity: u32, refill_per_sec: f64) -> Self {
+        assert!(capacity > 0, "token bucket capacity must be at least 1");
+        assert!(
+            refill_per_sec > 0.0,
+            "token bucket refill rate must be positive"
+        );
+
+        Self::with_pacing(
+            capacity as usize,
+            Pacing::TokenBucket {
+                tokens: capacity as f64,
+                capacity: capacity as f64,
+                refill_per_sec,
+                last_refill: Instant::now(),
+            },
+        )
+    }
+
+    fn with_pacing(max_concurrent: usize, pacing: Pacing) -> Self {
+        let configured_delay = match &pacing {
+            Pacing::FixedDelay { min_delay, .. } => *min_delay,
+            Pacing::TokenBucket { refill_per_sec, .. } => {
+                Duration::from_secs_f64(1.0 / refill_per_sec)
+            }
+        };
+
         Self {
-            last_request: Mutex::new(Instant::now() - Duration::from_secs(1)),
+            pacing: Mutex::new(pacing),
             semaphore: Semaphore::new(max_concurrent),
-            min_delay: Duration::from_millis(min_delay_ms),
+            configured_delay,
+            penalty_until: StdMutex::new(None),
+            acquisitions: AtomicU64::new(0),
+            throttled: AtomicU64::new(0),
+            sleep_nanos: AtomicU64::new(0),
+            penalties: AtomicU64::new(0),
+            penalty_nanos: AtomicU64::new(0),
         }
     }
 
+    /// Hold back every request through this limiter until `until`, e.g. for a 429's
+    /// `Retry-After`. Clients sharing the limiter all wait; an earlier deadline than the
+    /// current one is ignored.
+    pub fn penalize(&self, until: Instant) {
+        let mut penalty_until = self.penalty_until.lock().unwrap();
+        *penalty_until = Some(penalty_until.map_or(until, |current| current.max(until)));
+        self.penalties.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Time left until the current penalty deadline, if it is still ahead
+    fn penalty_remaining(&self) -> Duration {
+        self.penalty_until
+            .lock()
+            .unwrap()
+            .map_or(Duration::ZERO, |until| {
+                until.saturating_duration_since(Instant::now())
+            })
+    }
+
     pub async fn acquire(&self) {
         // Acquire semaphore permit to limit concurrency
         let _permit = self.semaphore.acquire().await.unwrap();
 
-        // Ensure minimum delay between requests
-        let mut last = self.last_request.lock().await;
-        let elapsed = last.elapsed();
-        if elapsed < self.min_delay {
-            tokio::time::sleep(self.min_delay - elapsed).await;
+        // Wait for our slot while holding the lock, so waiters are let through in order
+        let mut pacing = self.pacing.lock().await;
+
+        // A penalty may be extended while we wait it out, so check again after each sleep
+        let mut penalty = Duration::ZERO;
+        loop {
+            let remaining = self.penalty_remaining();
+            if remaining.is_zero() {
+                break;
+            }
+            tokio::time::sleep(remaining).await;
+            penalty += remaining;
+        }
+
+        let wait = pacing.reserve();
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+
+        let slept = wait + penalty;
+        if !slept.is_zero() {
+            self.throttled.fetch_add(1, Ordering::Relaxed);
+            self.sleep_nanos
+                .fetch_add(slept.as_nanos() as u64, Ordering::Relaxed);
+            self.penalty_nanos
+                .fetch_add(penalty.as_nanos() as u64, Ordering::Relaxed);
+        }
+        self.acquisitions.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Current counters; cheap enough to poll while requests are in flight
+    pub fn stats(&self) -> RateLimiterStats {
+        RateLimiterStats {
+            acquisitions: self.acquisitions.load(Ordering::Relaxed),
+            throttled: self.throttled.load(Ordering::Relaxed),
+            total_sleep: Duration::from_nanos(self.sleep_nanos.load(Ordering::Relaxed)),
+            penalties: self.penalties.load(Ordering::Relaxed),
+            total_penalty: Duration::from_nanos(self.penalty_nanos.load(Ordering::Relaxed)),
+            penalty_remaining: self.penalty_remaining(),
+            configured_delay: self.configured_delay,
         }
-        *last = Instant::now();
     }
 }
@@ -1,4 +1,5 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
 
 use crate::error::QueryValidationError;
 
@@ -8,6 +9,26 @@ pub struct QueryValidator {
     valid_fields: HashSet<&'static str>,
     valid_operators: HashSet<&'static str>,
     valid_comparisons: HashSet<&'static str>,
+    /// Lowercased set codes `set:` values are checked against; `None` skips the check
+    known_set_codes: Option<HashSet<String>>,
+}
+
+/// How serious a [`Lint`] is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LintSeverity {
+    /// Legal, but probably not what was meant
+    Warning,
+    /// [`QueryValidator::validate`] rejects the query
+    Error,
+}
+
+/// A diagnostic from [`QueryValidator::lint`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lint {
+    pub severity: LintSeverity,
+    pub message: String,
+    /// Byte range of the query the lint is about; the whole query for errors
+    pub span: Range<usize>,
 }
 
 impl QueryValidator {
@@ -31,8 +52,8 @@ impl QueryValidator {
             // Art and frames
             "art", "artist", "flavor", "ft", "watermark", "wm",
             // Misc
-            "year", "date", "lang", "game", "new", "order", "unique", "prefer",
-            "include", "border", "frame", "stamp", "keyword",
+            "year", "date", "lang", "game", "new", "order", "direction", "dir", "unique",
+            "prefer", "include", "border", "frame", "stamp", "keyword",
         ]
         .into_iter()
         .collect();
@@ -47,6 +68,28 @@ impl QueryValidator {
             valid_fields,
             valid_operators,
             valid_comparisons,
+            known_set_codes: None,
+        }
+    }
+
+    /// A validator that also rejects `set:`/`s:`/`e:`/`edition:` values not in `codes`, e.g.
+    /// the codes from [`ScryfallClient::fetch_sets`]. Set codes change with every release, so
+    /// the default validator never checks them.
+    ///
+    /// [`ScryfallClient::fetch_sets`]: crate::ScryfallClient::fetch_sets
+    pub fn with_set_codes<I, S>(codes: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        Self {
+            known_set_codes: Some(
+                codes
+                    .into_iter()
+                    .map(|code| code.as_ref().to_lowercase())
+                    .collect(),
+            ),
+            ..Self::new()
         }
     }
 
@@ -71,6 +114,12 @@ impl QueryValidator {
         // Check for operator positioning
         self.check_operator_positioning(trimmed)?;
 
+        // Check for "or"/"and" missing the spaces around it
+        self.check_glued_operators(trimmed)?;
+
+        // Check values of fields with a fixed syntax
+        self.check_field_values(trimmed)?;
+
         Ok(())
     }
 
@@ -111,9 +160,7 @@ impl QueryValidator {
         // Extract field:value patterns, excluding quoted strings
         let mut in_quotes = false;
         let mut current_field = String::new();
-        let mut chars = query.chars().peekable();
-
-        while let Some(ch) = chars.next() {
+        for ch in query.chars() {
             match ch {
                 '"' => {
                     in_quotes = !in_quotes;
@@ -157,17 +204,13 @@ impl QueryValidator {
         }
 
         // Check for leading "or" or "and" operators
-        if let Some(first) = words.first() {
-            if *first == "or" || *first == "and" {
-                return Err(QueryValidationError::LeadingOperator);
-            }
+        if words.first().is_some_and(|w| *w == "or" || *w == "and") {
+            return Err(QueryValidationError::LeadingOperator);
         }
 
         // Check for trailing "or" or "and" operators
-        if let Some(last) = words.last() {
-            if *last == "or" || *last == "and" {
-                return Err(QueryValidationError::TrailingOperator);
-            }
+        if words.last().is_some_and(|w| *w == "or" || *w == "and") {
+            return Err(QueryValidationError::TrailingOperator);
         }
 
         // Check for consecutive operators
@@ -183,6 +226,225 @@ impl QueryValidator {
         Ok(())
     }
 
+    /// Catch `or`/`and` written without spaces, which Scryfall reads as part of a word. Only
+    /// flags clear boundaries so names like "Gruulor" pass: in front of another field
+    /// (`c:redorc:blue`) or after a whole card type or color (`t:creatureor`). An operator
+    /// against a parenthesis (`t:elf or(t:goblin)`) still parses, so [`lint`](Self::lint)
+    /// only warns about it.
+    fn check_glued_operators(&self, query: &str) -> Result<(), QueryValidationError> {
+        for token in tokens(query) {
+            let Some(term) = parse_term(token) else {
+                continue;
+            };
+            let value = term.value.to_lowercase();
+
+            // `c:redorc:blue`: the value runs into another `field:`
+            if let Some(next) = value.find([':', '=', '<', '>', '!']) {
+                let before_next = &value[..next];
+                for op in ["or", "and"] {
+                    let glued = before_next.rfind(op).is_some_and(|i| {
+                        i > 0
+                            && self
+                                .valid_fields
+                                .contains(before_next[i + op.len()..].trim_start_matches('-'))
+                    });
+                    if glued {
+                        return Err(QueryValidationError::GluedOperator(term.text));
+                    }
+                }
+            }
+
+            // `t:creatureor`: a complete type or color followed by an operator
+            let known: &[&str] = match term.field.as_str() {
+                "t" | "type" => CARD_TYPES,
+                "c" | "color" | "id" | "identity" | "ci" => COLOR_NAMES,
+                _ => continue,
+            };
+            for op in ["or", "and"] {
+                let glued = value
+                    .strip_suffix(op)
+                    .is_some_and(|prefix| known.contains(&prefix) && !known.contains(&&*value));
+                if glued {
+                    return Err(QueryValidationError::GluedOperator(term.text));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn check_field_values(&self, query: &str) -> Result<(), QueryValidationError> {
+        for term in terms(query) {
+            let allowed: &[&str] = match term.field.as_str() {
+                "m" | "mana" => {
+                    check_mana_value(&term)?;
+                    continue;
+                }
+                "s" | "set" | "e" | "edition" => {
+                    self.check_set_code(&term)?;
+                    continue;
+                }
+                "order" => ORDER_VALUES,
+                "direction" | "dir" => DIRECTION_VALUES,
+                "unique" => UNIQUE_VALUES,
+                _ => continue,
+            };
+            if !allowed.contains(&term.value.to_lowercase().as_str()) {
+                return Err(QueryValidationError::InvalidComparison(term.text));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn check_set_code(&self, term: &Term) -> Result<(), QueryValidationError> {
+        let Some(known) = &self.known_set_codes else {
+            return Ok(());
+        };
+        let code = term.value.to_lowercase();
+        if known.contains(&code) {
+            return Ok(());
+        }
+
+        // Suggest the closest known code, if it is a plausible typo
+        let suggestion = known
+            .iter()
+            .map(|candidate| (edit_distance(&code, candidate), candidate))
+            .filter(|(distance, _)| *distance <= 2)
+            .min();
+
+        Err(QueryValidationError::InvalidField(match suggestion {
+            Some((_, candidate)) => format!("{} (did you mean {}?)", term.text, candidate),
+            None => term.text.clone(),
+        }))
+    }
+
+    /// Diagnostics for a query, for hints that should not block submission. The `validate`
+    /// error, if any, comes first as an `Error`; `Warning`s flag legal but suspicious patterns:
+    /// a lone `-`, an unquoted multi-word value after a text field (`o:draw a card` only
+    /// searches rules text for "draw"), `or`/`and` written against a parenthesis
+    /// (`t:elf or(t:goblin)`), and parentheses directly inside parentheses.
+    pub fn lint(&self, query: &str) -> Vec<Lint> {
+        let mut lints = Vec::new();
+        if let Err(e) = self.validate(query) {
+            lints.push(Lint {
+                severity: LintSeverity::Error,
+                message: e.to_string(),
+                span: 0..query.len(),
+            });
+        }
+
+        let warning = |message: String, span: Range<usize>| Lint {
+            severity: LintSeverity::Warning,
+            message,
+            span,
+        };
+
+        let spans = token_spans(query);
+        for (i, span) in spans.iter().enumerate() {
+            let token = &query[span.clone()];
+            if token == "-" {
+                lints.push(warning(
+                    "A lone '-' negates nothing; attach it to a term, e.g. -t:goblin".to_string(),
+                    span.clone(),
+                ));
+                continue;
+            }
+
+            let Some(term) = parse_term(token.to_string()) else {
+                continue;
+            };
+            if !TEXT_FIELDS.contains(&term.field.as_str())
+                || term.value.is_empty()
+                || term.text.contains('"')
+            {
+                continue;
+            }
+            let Some(next) = spans.get(i + 1) else {
+                continue;
+            };
+            let next_word = &query[next.clone()];
+            let bare_word = parse_term(next_word.to_string()).is_none()
+                && !next_word.starts_with('-')
+                && !["or", "and"].contains(&next_word.to_lowercase().as_str());
+            if bare_word && query[span.end..next.start].trim().is_empty() {
+                // The field and comparison as written, e.g. `-o:`
+                let prefix = &token[..token.len() - term.value.len()];
+                lints.push(warning(
+                    format!(
+                        "Only '{}' is searched by {}, '{}' searches card names; quote \
+                         multi-word values, e.g. {}\"{} {}\"",
+                        term.value, prefix, next_word, prefix, term.value, next_word
+                    ),
+                    span.start..next.end,
+                ));
+            }
+        }
+
+        for span in paren_glued_operators(query) {
+            let op = &query[span.clone()];
+            lints.push(warning(
+                format!(
+                    "'{}' is written against a parenthesis; spaces around it read more clearly, \
+                     e.g. ') {} ('",
+                    op, op
+                ),
+                span,
+            ));
+        }
+
+        let pairs = paren_pairs(query);
+        for (&open, &close) in &pairs {
+            if pairs.get(&(open + 1)) == Some(&(close - 1)) {
+                lints.push(warning(
+                    "Redundant parentheses around a group that is already parenthesized"
+                        .to_string(),
+                    open..close + 1,
+                ));
+            }
+        }
+        lints.sort_by_key(|lint| (lint.severity == LintSeverity::Warning, lint.span.start));
+
+        lints
+    }
+
+    /// Split a query into its top-level clauses at spaces, keeping parenthesized groups and
+    /// quoted values whole: `(c:red or c:blue) cmc<=3 -is:reprint` gives `(c:red or c:blue)`,
+    /// `cmc<=3` and `-is:reprint`. A top-level `or`/`and` is a clause of its own.
+    pub fn split_clauses(&self, query: &str) -> Vec<String> {
+        let mut clauses = Vec::new();
+        let mut current = String::new();
+        let mut depth = 0i32;
+        let mut in_quotes = false;
+
+        for ch in query.chars() {
+            match ch {
+                '"' => in_quotes = !in_quotes,
+                '(' if !in_quotes => depth += 1,
+                ')' if !in_quotes => depth -= 1,
+                _ => {}
+            }
+            if ch.is_whitespace() && !in_quotes && depth <= 0 {
+                if !current.is_empty() {
+                    clauses.push(std::mem::take(&mut current));
+                }
+            } else {
+                current.push(ch);
+            }
+        }
+        if !current.is_empty() {
+            clauses.push(current);
+        }
+
+        clauses
+    }
+
+    /// Validate a query and return it URL-encoded, ready to put in a request URL
+    pub fn validate_and_encode(&self, query: &str) -> Result<String, QueryValidationError> {
+        self.validate(query)?;
+        Ok(self.encode_query(query))
+    }
+
     /// URL-encode a validated query for use in API requests
     pub fn encode_query(&self, query: &str) -> String {
         urlencoding::encode(query).into_owned()
@@ -194,3 +456,246 @@ impl Default for QueryValidator {
         Self::new()
     }
 }
+
+/// Sort orders accepted by `order:`
+const ORDER_VALUES: &[&str] = &[
+    "name",
+    "set",
+    "released",
+    "rarity",
+    "color",
+    "usd",
+    "tix",
+    "eur",
+    "cmc",
+    "power",
+    "toughness",
+    "edhrec",
+    "penny",
+    "artist",
+    "review",
+    "spoiled",
+];
+
+/// Sort directions accepted by `direction:`/`dir:`
+const DIRECTION_VALUES: &[&str] = &["asc", "desc", "auto"];
+
+/// Modes accepted by `unique:`
+const UNIQUE_VALUES: &[&str] = &["cards", "prints", "art"];
+
+/// Card types and supertypes, for spotting an operator glued to a `t:` value
+const CARD_TYPES: &[&str] = &[
+    "artifact",
+    "battle",
+    "creature",
+    "enchantment",
+    "instant",
+    "land",
+    "planeswalker",
+    "sorcery",
+    "kindred",
+    "tribal",
+    "legendary",
+    "basic",
+    "snow",
+    "token",
+];
+
+/// Color words, for spotting an operator glued to a `c:` value
+const COLOR_NAMES: &[&str] = &[
+    "w",
+    "u",
+    "b",
+    "r",
+    "g",
+    "c",
+    "m",
+    "white",
+    "blue",
+    "black",
+    "red",
+    "green",
+    "colorless",
+    "multicolor",
+];
+
+/// Fields whose value is free text, so an unquoted multi-word value is cut off at the first
+/// space
+const TEXT_FIELDS: &[&str] = &["o", "oracle", "t", "type", "ft", "flavor", "artist"];
+
+/// A `field:value` term of a query, e.g. `-t:"legendary creature"`
+struct Term {
+    /// The term as written, for error messages
+    text: String,
+    /// Lowercased field name without a leading `-`
+    field: String,
+    /// Value after the comparison, with surrounding quotes removed
+    value: String,
+}
+
+/// Split a query into tokens at whitespace and parentheses outside quotes
+fn tokens(query: &str) -> Vec<String> {
+    token_spans(query)
+        .into_iter()
+        .map(|span| query[span].to_string())
+        .collect()
+}
+
+/// Byte ranges of the tokens [`tokens`] returns
+fn token_spans(query: &str) -> Vec<Range<usize>> {
+    let mut spans = Vec::new();
+    let mut start = None;
+    let mut in_quotes = false;
+
+    for (i, ch) in query.char_indices() {
+        match ch {
+            ' ' | '(' | ')' if !in_quotes => {
+                if let Some(start) = start.take() {
+                    spans.push(start..i);
+                }
+            }
+            _ => {
+                if ch == '"' {
+                    in_quotes = !in_quotes;
+                }
+                start.get_or_insert(i);
+            }
+        }
+    }
+    if let Some(start) = start {
+        spans.push(start..query.len());
+    }
+
+    spans
+}
+
+/// Byte offset of each `(` outside quotes mapped to its matching `)`; unmatched ones are left out
+fn paren_pairs(query: &str) -> HashMap<usize, usize> {
+    let mut pairs = HashMap::new();
+    let mut open = Vec::new();
+    let mut in_quotes = false;
+
+    for (i, ch) in query.char_indices() {
+        match ch {
+            '"' => in_quotes = !in_quotes,
+            '(' if !in_quotes => open.push(i),
+            ')' if !in_quotes => {
+                if let Some(start) = open.pop() {
+                    pairs.insert(start, i);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pairs
+}
+
+/// Byte ranges of each `or`/`and` outside quotes written directly against a parenthesis, as in
+/// `t:elf or(t:goblin)` or `(t:elf)or t:goblin`
+fn paren_glued_operators(query: &str) -> Vec<Range<usize>> {
+    // ASCII lowercasing keeps byte offsets valid for `query`
+    let lowered = query.to_ascii_lowercase();
+    let is_word_char = |c: Option<char>| c.is_some_and(|c| c.is_alphanumeric());
+
+    let mut spans = Vec::new();
+    for op in ["or", "and"] {
+        for (i, _) in lowered.match_indices(op) {
+            let in_quotes = lowered[..i].matches('"').count() % 2 == 1;
+            let before = lowered[..i].chars().next_back();
+            let after = lowered[i + op.len()..].chars().next();
+            let glued = (before == Some(')') && !is_word_char(after))
+                || (after == Some('(') && !is_word_char(before));
+            if glued && !in_quotes {
+                spans.push(i..i + op.len());
+            }
+        }
+    }
+    spans
+}
+
+/// Parse a token as a `field:value` term; bare words give `None`
+fn parse_term(token: String) -> Option<Term> {
+    let start = token.find([':', '=', '<', '>', '!'])?;
+    if token[..start].contains('"') {
+        return None;
+    }
+    let field = token[..start].trim_start_matches('-').to_lowercase();
+    let value = token[start..]
+        .trim_start_matches([':', '=', '<', '>', '!'])
+        .trim_matches('"')
+        .to_string();
+    Some(Term {
+        text: token,
+        field,
+        value,
+    })
+}
+
+/// Split a query into its `field:value` terms, skipping bare words
+fn terms(query: &str) -> Vec<Term> {
+    tokens(query).into_iter().filter_map(parse_term).collect()
+}
+
+/// Check an `m:`/`mana:` value: `{...}` symbols must be balanced and valid; unbraced shorthand
+/// like `2WW` is left to Scryfall
+fn check_mana_value(term: &Term) -> Result<(), QueryValidationError> {
+    let invalid = || QueryValidationError::InvalidComparison(term.text.clone());
+    let mut rest = term.value.as_str();
+
+    while let Some(ch) = rest.chars().next() {
+        if ch == '{' {
+            let end = rest.find('}').ok_or_else(invalid)?;
+            if !is_mana_symbol(&rest[1..end]) {
+                return Err(invalid());
+            }
+            rest = &rest[end + 1..];
+        } else if ch.is_ascii_alphanumeric() {
+            rest = &rest[1..];
+        } else {
+            return Err(invalid());
+        }
+    }
+
+    Ok(())
+}
+
+/// Whether `symbol` (the text between braces) is a mana symbol: generic (`2`), colored or
+/// special (`W`, `C`, `X`, `S`), hybrid (`W/U`, `2/W`, `C/W`) or Phyrexian (`G/P`, `G/U/P`)
+fn is_mana_symbol(symbol: &str) -> bool {
+    let symbol = symbol.to_ascii_uppercase();
+    let is_color = |s: &str| matches!(s, "W" | "U" | "B" | "R" | "G");
+    let parts: Vec<&str> = symbol.split('/').collect();
+
+    match parts.as_slice() {
+        [single] => {
+            (!single.is_empty() && single.chars().all(|c| c.is_ascii_digit()))
+                || is_color(single)
+                || matches!(*single, "C" | "X" | "Y" | "Z" | "S")
+        }
+        [first, second] => {
+            (is_color(first) || matches!(*first, "2" | "C"))
+                && (is_color(second) || *second == "P")
+                && first != second
+        }
+        [first, second, "P"] => is_color(first) && is_color(second) && first != second,
+        _ => false,
+    }
+}
+
+/// Levenshtein distance between two strings
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = prev[j] + usize::from(ca != *cb);
+            current.push(substitution.min(prev[j + 1] + 1).min(current[j] + 1));
+        }
+        prev = current;
+    }
+
+    prev[b.len()]
+}
@@ -0,0 +1,999 @@
+use futures::{StreamExt, TryStreamExt};
+use scripts::{
+    CardIdentifier, FetchLimits, Progress, QueryValidationError, RateLimiter, ScryfallClient,
+    ScryfallError,
+};
+use serde_json::json;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+use wiremock::matchers::{header, method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn card_json(id: &str, name: &str) -> serde_json::Value {
+    json!({
+        "object": "card",
+        "id": id,
+        "name": name,
+        "set_name": "Test Set",
+        "rarity": "common"
+    })
+}
+
+fn client_for(server: &MockServer) -> ScryfallClient {
+    ScryfallClient::new().with_base_url(server.uri())
+}
+
+// ==================== Base URL Tests ====================
+
+#[test]
+fn test_default_base_url() {
+    let client = ScryfallClient::new();
+    assert_eq!(client.base_url(), "https://api.scryfall.com");
+}
+
+#[test]
+fn test_base_url_trailing_slash_trimmed() {
+    let client = ScryfallClient::new().with_base_url("http://localhost:1234/");
+    assert_eq!(client.base_url(), "http://localhost:1234");
+}
+
+// ==================== Pagination Tests ====================
+
+#[tokio::test]
+async fn test_fetch_all_json_follows_next_page() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/cards/search"))
+        .and(query_param("q", "c:red"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "object": "list",
+            "total_cards": 2,
+            "has_more": true,
+            "next_page": format!("{}/cards/search?q=c%3Ared&page=2", server.uri()),
+            "data": [card_json("1", "Shock")]
+        })))
+        .up_to_n_times(1)
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/cards/search"))
+        .and(query_param("page", "2"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "object": "list",
+            "total_cards": 2,
+            "has_more": false,
+            "data": [card_json("2", "Lightning Bolt")]
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = client_for(&server);
+    let pages = client
+        .fetch_all_json("c:red", FetchLimits::none())
+        .await
+        .unwrap()
+        .data;
+
+    assert_eq!(pages.len(), 2);
+    assert_eq!(pages[1]["data"][0]["name"], "Lightning Bolt");
+}
+
+#[tokio::test]
+async fn test_fetch_all_cards_collects_every_page() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/cards/search"))
+        .and(query_param("page", "2"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "object": "list",
+            "total_cards": 2,
+            "has_more": false,
+            "data": [card_json("2", "Lightning Bolt")]
+        })))
+        .mount(&server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/cards/search"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "object": "list",
+            "total_cards": 2,
+            "has_more": true,
+            "next_page": format!("{}/cards/search?q=c%3Ared&page=2", server.uri()),
+            "data": [card_json("1", "Shock")]
+        })))
+        .mount(&server)
+        .await;
+
+    let client = client_for(&server);
+    let cards = client
+        .fetch_all_cards("c:red", FetchLimits::none())
+        .await
+        .unwrap()
+        .data;
+
+    let names: Vec<_> = cards.iter().map(|c| c.name.as_str()).collect();
+    assert_eq!(names, ["Shock", "Lightning Bolt"]);
+}
+
+#[tokio::test]
+async fn test_invalid_query_sends_no_request() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&server)
+        .await;
+
+    let client = client_for(&server);
+    let result = client.fetch_all_json("(c:red", FetchLimits::none()).await;
+
+    assert!(matches!(result, Err(ScryfallError::ValidationError(_))));
+}
+
+/// Mounts a search endpoint whose every page claims there is another page after it
+async fn mount_endless_search(server: &MockServer, expected_requests: u64) {
+    Mock::given(method("GET"))
+        .and(path("/cards/search"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "object": "list",
+            "total_cards": 100000,
+            "has_more": true,
+            "next_page": format!("{}/cards/search?q=o%3Aa&page=next", server.uri()),
+            "data": [card_json("1", "Shock"), card_json("2", "Lightning Bolt")]
+        })))
+        .expect(expected_requests)
+        .mount(server)
+        .await;
+}
+
+#[tokio::test]
+async fn test_max_pages_stops_fetching() {
+    let server = MockServer::start().await;
+    mount_endless_search(&server, 3).await;
+
+    let client = client_for(&server);
+    let outcome = client
+        .fetch_all_json("o:a", FetchLimits::max_pages(3))
+        .await
+        .unwrap();
+
+    assert_eq!(outcome.data.len(), 3);
+    assert!(outcome.truncated);
+}
+
+#[tokio::test]
+async fn test_max_cards_truncates_exactly() {
+    let server = MockServer::start().await;
+    mount_endless_search(&server, 3).await;
+
+    let client = client_for(&server);
+    let outcome = client
+        .fetch_all_cards("o:a", FetchLimits::max_cards(5))
+        .await
+        .unwrap();
+
+    assert_eq!(outcome.data.len(), 5);
+    assert!(outcome.truncated);
+}
+
+#[tokio::test]
+async fn test_complete_fetch_is_not_truncated() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/cards/search"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "object": "list",
+            "total_cards": 1,
+            "has_more": false,
+            "data": [card_json("1", "Shock")]
+        })))
+        .mount(&server)
+        .await;
+
+    let client = client_for(&server);
+    let outcome = client
+        .fetch_all_cards("c:red", FetchLimits::max_pages(1))
+        .await
+        .unwrap();
+
+    assert_eq!(outcome.data.len(), 1);
+    assert!(!outcome.truncated);
+}
+
+#[tokio::test]
+async fn test_paginated_fetches_collect_warnings_once() {
+    let server = MockServer::start().await;
+    let ignored = "Invalid expression \u{201c}x:y\u{201d} was ignored.";
+
+    Mock::given(method("GET"))
+        .and(path("/cards/search"))
+        .and(query_param("page", "2"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "object": "list",
+            "total_cards": 2,
+            "has_more": false,
+            "warnings": [ignored, "Unknown unique mode."],
+            "data": [card_json("2", "Lightning Bolt")]
+        })))
+        .mount(&server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/cards/search"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "object": "list",
+            "total_cards": 2,
+            "has_more": true,
+            "next_page": format!("{}/cards/search?q=c%3Ared&page=2", server.uri()),
+            "warnings": [ignored],
+            "data": [card_json("1", "Shock")]
+        })))
+        .mount(&server)
+        .await;
+
+    let client = client_for(&server);
+    let expected = [ignored, "Unknown unique mode."];
+
+    let json = client
+        .fetch_all_json("c:red", FetchLimits::none())
+        .await
+        .unwrap();
+    assert_eq!(json.warnings, expected);
+
+    let cards = client
+        .fetch_all_cards("c:red", FetchLimits::none())
+        .await
+        .unwrap();
+    assert_eq!(cards.data.len(), 2);
+    assert_eq!(cards.warnings, expected);
+}
+
+#[tokio::test]
+async fn test_search_page_hands_back_cursor() {
+    let server = MockServer::start().await;
+    let next = format!("{}/cards/search?q=c%3Ared&page=2", server.uri());
+
+    Mock::given(method("GET"))
+        .and(path("/cards/search"))
+        .and(query_param("page", "2"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "object": "list",
+            "total_cards": 2,
+            "has_more": false,
+            "data": [card_json("2", "Lightning Bolt")]
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/cards/search"))
+        .and(query_param("q", "c:red"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "object": "list",
+            "total_cards": 2,
+            "has_more": true,
+            "next_page": next,
+            "data": [card_json("1", "Shock")]
+        })))
+        .expect(2)
+        .mount(&server)
+        .await;
+
+    let client = client_for(&server);
+
+    let first = client.search_page("c:red", None).await.unwrap();
+    assert_eq!(first.cards[0].name, "Shock");
+    assert_eq!(first.total_cards, 2);
+    assert!(first.has_more);
+    assert_eq!(first.next_page.as_deref(), Some(next.as_str()));
+
+    let second = client
+        .search_page("c:red", first.next_page.as_deref())
+        .await
+        .unwrap();
+    assert_eq!(second.cards[0].name, "Lightning Bolt");
+    assert!(!second.has_more);
+    assert!(second.next_page.is_none());
+
+    // Starting over goes back to page 1; an invalid query never reaches the API
+    assert!(client.search_page("c:red", None).await.unwrap().has_more);
+    assert!(matches!(
+        client.search_page("(c:red", None).await,
+        Err(ScryfallError::ValidationError(_))
+    ));
+}
+
+// ==================== Progress Tests ====================
+
+/// Reduces page events to `(page, cards)` pairs, with `cards` unset for `FetchingPage`
+fn page_events(events: &Mutex<Vec<Progress>>) -> Vec<(u32, Option<usize>)> {
+    events
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|event| match event {
+            Progress::FetchingPage { page } => (*page, None),
+            Progress::PageFetched { page, cards, .. } => (*page, Some(*cards)),
+            other => panic!("unexpected event {:?}", other),
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_fetch_all_reports_pages_in_order() {
+    let server = MockServer::start().await;
+    mount_endless_search(&server, 4).await;
+    let client = client_for(&server);
+    let expected = [(1, None), (1, Some(2)), (2, None), (2, Some(2))];
+
+    let events = Mutex::new(Vec::new());
+    client
+        .fetch_all_json_with_progress("o:a", FetchLimits::max_pages(2), |event| {
+            events.lock().unwrap().push(event)
+        })
+        .await
+        .unwrap();
+    assert_eq!(page_events(&events), expected);
+
+    let events = Mutex::new(Vec::new());
+    client
+        .fetch_all_cards_with_progress("o:a", FetchLimits::max_pages(2), |event| {
+            events.lock().unwrap().push(event)
+        })
+        .await
+        .unwrap();
+    assert_eq!(page_events(&events), expected);
+}
+
+// ==================== Single Card Tests ====================
+
+#[tokio::test]
+async fn test_fetch_card_named_exact() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/cards/named"))
+        .and(query_param("exact", "Lightning Bolt"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(card_json("2", "Lightning Bolt")))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = client_for(&server);
+    let card = client
+        .fetch_card_named("Lightning Bolt", false)
+        .await
+        .unwrap();
+
+    assert_eq!(card["id"], "2");
+}
+
+#[tokio::test]
+async fn test_fetch_card_named_not_found() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/cards/named"))
+        .respond_with(ResponseTemplate::new(404).set_body_json(json!({
+            "object": "error",
+            "code": "not_found",
+            "status": 404,
+            "details": "No cards found matching \u{201c}Lightnig Blot\u{201d}"
+        })))
+        .mount(&server)
+        .await;
+
+    let client = client_for(&server);
+    let result = client.fetch_card_named("Lightnig Blot", true).await;
+
+    match result {
+        Err(ScryfallError::NotFound(details)) => assert!(details.starts_with("No cards found")),
+        other => panic!("expected NotFound, got {:?}", other),
+    }
+}
+
+// ==================== Autocomplete Tests ====================
+
+#[tokio::test]
+async fn test_autocomplete_returns_names() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/cards/autocomplete"))
+        .and(query_param("q", "light"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "object": "catalog",
+            "total_values": 2,
+            "data": ["Lightning Bolt", "Lightning Helix"]
+        })))
+        .mount(&server)
+        .await;
+
+    let client = client_for(&server);
+    let names = client.autocomplete("light").await.unwrap();
+
+    assert_eq!(names, ["Lightning Bolt", "Lightning Helix"]);
+}
+
+#[tokio::test]
+async fn test_autocomplete_blank_input_sends_no_request() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&server)
+        .await;
+
+    let client = client_for(&server);
+
+    assert!(client.autocomplete("   ").await.unwrap().is_empty());
+}
+
+// ==================== Collection Tests ====================
+
+#[tokio::test]
+async fn test_fetch_collection_chunks_requests() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/cards/collection"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "object": "list",
+            "not_found": [{ "name": "Missing Card" }],
+            "data": [card_json("1", "Shock")]
+        })))
+        .expect(2)
+        .mount(&server)
+        .await;
+
+    let identifiers: Vec<_> = (0..80)
+        .map(|i| CardIdentifier::Id { id: i.to_string() })
+        .collect();
+
+    let client = client_for(&server);
+    let collection = client.fetch_collection(&identifiers).await.unwrap();
+
+    assert_eq!(collection.data.len(), 2);
+    assert_eq!(collection.not_found.len(), 2);
+}
+
+#[test]
+fn test_card_identifier_serialization() {
+    let by_number = CardIdentifier::SetAndNumber {
+        set: "neo".into(),
+        collector_number: "123".into(),
+    };
+    assert_eq!(
+        serde_json::to_value(&by_number).unwrap(),
+        json!({ "set": "neo", "collector_number": "123" })
+    );
+
+    let by_id = CardIdentifier::Id { id: "abc".into() };
+    assert_eq!(
+        serde_json::to_value(&by_id).unwrap(),
+        json!({ "id": "abc" })
+    );
+}
+
+// ==================== Error Mapping Tests ====================
+
+#[tokio::test]
+async fn test_api_error_keeps_status_and_details() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/cards/search"))
+        .respond_with(ResponseTemplate::new(422).set_body_json(json!({
+            "object": "error",
+            "code": "bad_request",
+            "status": 422,
+            "details": "All of your terms were ignored.",
+            "warnings": ["Invalid expression \u{201c}x:y\u{201d} was ignored."]
+        })))
+        .mount(&server)
+        .await;
+
+    let client = client_for(&server);
+    let result = client.fetch_all_json("x:y", FetchLimits::none()).await;
+
+    match result {
+        Err(ScryfallError::ApiError {
+            status,
+            details,
+            warnings,
+        }) => {
+            assert_eq!(status, 422);
+            assert_eq!(details, "All of your terms were ignored.");
+            assert_eq!(warnings.len(), 1);
+        }
+        other => panic!("expected ApiError, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_api_error_without_json_body() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/cards/search"))
+        .respond_with(ResponseTemplate::new(503).set_body_string("upstream down"))
+        .mount(&server)
+        .await;
+
+    let client = client_for(&server);
+    let result = client.fetch_all_json("c:red", FetchLimits::none()).await;
+
+    assert!(matches!(
+        result,
+        Err(ScryfallError::ApiError { status: 503, .. })
+    ));
+}
+
+// ==================== Cancellation Tests ====================
+
+#[tokio::test]
+async fn test_cancelled_client_stops_before_fetching() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&server)
+        .await;
+
+    let token = CancellationToken::new();
+    token.cancel();
+
+    let client = client_for(&server).with_cancellation_token(token);
+    let outcome = client
+        .fetch_all_json("c:red", FetchLimits::none())
+        .await
+        .unwrap();
+
+    assert!(client.is_cancelled());
+    assert!(outcome.truncated);
+    assert!(outcome.data.is_empty());
+}
+
+// ==================== Streaming Tests ====================
+
+#[tokio::test]
+async fn test_fetch_card_stream_yields_cards_across_pages() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/cards/search"))
+        .and(query_param("page", "2"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "object": "list",
+            "total_cards": 3,
+            "has_more": false,
+            "data": [card_json("3", "Fireblast")]
+        })))
+        .mount(&server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/cards/search"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "object": "list",
+            "total_cards": 3,
+            "has_more": true,
+            "next_page": format!("{}/cards/search?q=c%3Ared&page=2", server.uri()),
+            "data": [card_json("1", "Shock"), card_json("2", "Lightning Bolt")]
+        })))
+        .mount(&server)
+        .await;
+
+    let client = client_for(&server);
+    let cards: Vec<_> = client
+        .fetch_card_stream("c:red")
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+
+    let ids: Vec<_> = cards.iter().map(|c| c.id.as_str()).collect();
+    assert_eq!(ids, ["1", "2", "3"]);
+}
+
+#[tokio::test]
+async fn test_fetch_card_stream_validates_up_front() {
+    let client = ScryfallClient::new();
+    assert!(matches!(
+        client.fetch_card_stream("c:red or"),
+        Err(ScryfallError::ValidationError(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_fetch_card_stream_ends_after_error() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/cards/search"))
+        .respond_with(ResponseTemplate::new(500))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = client_for(&server);
+    let results: Vec<_> = client.fetch_card_stream("c:red").unwrap().collect().await;
+
+    assert_eq!(results.len(), 1);
+    assert!(results[0].is_err());
+}
+
+// ==================== Rulings Tests ====================
+
+#[tokio::test]
+async fn test_fetch_rulings_returns_data() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/cards/abc/rulings"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "object": "list",
+            "has_more": false,
+            "data": [{
+                "object": "ruling",
+                "source": "wotc",
+                "published_at": "2004-10-04",
+                "comment": "It can target any creature."
+            }]
+        })))
+        .mount(&server)
+        .await;
+
+    let client = client_for(&server);
+    let rulings = client.fetch_rulings("abc").await.unwrap();
+
+    assert_eq!(rulings.len(), 1);
+    assert_eq!(rulings[0]["source"], "wotc");
+}
+
+#[tokio::test]
+async fn test_fetch_rulings_unknown_card() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(&server)
+        .await;
+
+    let client = client_for(&server);
+    let result = client.fetch_rulings("missing").await;
+
+    assert!(matches!(result, Err(ScryfallError::NotFound(_))));
+}
+
+// ==================== Multiple Query Tests ====================
+
+#[tokio::test]
+async fn test_fetch_multiple_queries_deduped() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/cards/search"))
+        .and(query_param("q", "c:red"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "object": "list",
+            "total_cards": 2,
+            "has_more": false,
+            "data": [card_json("1", "Shock"), card_json("2", "Lightning Bolt")]
+        })))
+        .mount(&server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/cards/search"))
+        .and(query_param("q", "cmc<=1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "object": "list",
+            "total_cards": 2,
+            "has_more": false,
+            "data": [card_json("2", "Lightning Bolt"), card_json("3", "Opt")]
+        })))
+        .mount(&server)
+        .await;
+
+    let client = client_for(&server);
+    let deduped = client
+        .fetch_multiple_queries_deduped(vec!["c:red", "cmc<=1", "(broken"])
+        .await;
+
+    let ids: Vec<_> = deduped.cards.iter().map(|c| c.id.as_str()).collect();
+    assert_eq!(ids, ["1", "2", "3"]);
+    assert_eq!(deduped.failures.len(), 1);
+    assert_eq!(deduped.failures[0].0, "(broken");
+}
+
+#[tokio::test]
+async fn test_plan_queries_without_network() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(500))
+        .expect(0)
+        .mount(&server)
+        .await;
+
+    let client = client_for(&server);
+    let plans = client.plan_queries(&["c:red t:goblin", "(broken"]);
+
+    assert!(plans[0].valid);
+    assert!(plans[0].error.is_none());
+    assert_eq!(
+        plans[0].encoded_url.as_deref(),
+        Some(format!("{}/cards/search?q=c%3Ared%20t%3Agoblin", server.uri()).as_str())
+    );
+    assert_eq!(plans[1].query, "(broken");
+    assert!(!plans[1].valid);
+    assert!(matches!(
+        plans[1].error,
+        Some(QueryValidationError::UnbalancedParentheses)
+    ));
+    assert!(plans[1].encoded_url.is_none());
+}
+
+// ==================== Rate Limiter Tests ====================
+
+#[tokio::test]
+async fn test_clients_share_rate_limiter() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/cards/autocomplete"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "object": "catalog",
+            "total_values": 0,
+            "data": []
+        })))
+        .mount(&server)
+        .await;
+
+    // Long enough that a request under a loaded test run still finishes inside the delay
+    let limiter = Arc::new(RateLimiter::new(1, 200));
+    let first = client_for(&server).with_rate_limiter(limiter.clone());
+    let second = client_for(&server).with_rate_limiter(limiter.clone());
+
+    first.autocomplete("bolt").await.unwrap();
+    second.autocomplete("bolt").await.unwrap();
+    first.autocomplete("bolt").await.unwrap();
+
+    assert_eq!(limiter.stats().acquisitions, 3);
+    assert_eq!(second.rate_limiter_stats().acquisitions, 3);
+    // Without sharing, each client's first request would go straight through
+    assert!(limiter.stats().throttled >= 2);
+}
+
+// ==================== Builder Tests ====================
+
+#[tokio::test]
+async fn test_builder_sets_user_agent_and_base_url() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/cards/autocomplete"))
+        .and(header("user-agent", "DeckTool/2.0 (decks@example.com)"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "object": "catalog",
+            "total_values": 1,
+            "data": ["Opt"]
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = ScryfallClient::builder()
+        .user_agent("DeckTool/2.0 (decks@example.com)")
+        .base_url(format!("{}/", server.uri()))
+        .rate_limit_delay(Duration::from_millis(10))
+        .build()
+        .unwrap();
+
+    assert_eq!(client.base_url(), server.uri());
+    assert_eq!(client.autocomplete("op").await.unwrap(), ["Opt"]);
+    assert_eq!(
+        client.rate_limiter_stats().configured_delay,
+        Duration::from_millis(10)
+    );
+}
+
+#[test]
+fn test_builder_rejects_invalid_user_agent() {
+    let result = ScryfallClient::builder().user_agent("bad\nagent").build();
+    assert!(matches!(result, Err(ScryfallError::InvalidConfig(_))));
+}
+
+#[tokio::test]
+async fn test_from_lookup_reads_scryfall_variables() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/cards/autocomplete"))
+        .and(header("user-agent", "EnvTool/1.0"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "object": "catalog",
+            "total_values": 1,
+            "data": ["Opt"]
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    // A lookup over fixed variables, so the process environment is never modified
+    let lookup = |vars: &'static [(&'static str, &'static str)]| {
+        move |name: &str| {
+            vars.iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| value.to_string())
+        }
+    };
+
+    let client = ScryfallClient::from_lookup(lookup(&[
+        ("SCRYFALL_USER_AGENT", "EnvTool/1.0"),
+        ("SCRYFALL_RATE_LIMIT_MS", "25"),
+        ("SCRYFALL_MAX_CONCURRENT", "2"),
+    ]))
+    .unwrap()
+    .with_base_url(server.uri());
+    assert_eq!(client.autocomplete("op").await.unwrap(), ["Opt"]);
+    assert_eq!(
+        client.rate_limiter_stats().configured_delay,
+        Duration::from_millis(25)
+    );
+
+    // Empty values keep the defaults
+    let client = ScryfallClient::from_lookup(lookup(&[("SCRYFALL_RATE_LIMIT_MS", "")])).unwrap();
+    assert_eq!(
+        client.rate_limiter_stats().configured_delay,
+        Duration::from_millis(100)
+    );
+
+    let result = ScryfallClient::from_lookup(lookup(&[("SCRYFALL_RATE_LIMIT_MS", "fast")]));
+    assert!(matches!(result, Err(ScryfallError::InvalidConfig(_))));
+
+    let result = ScryfallClient::from_lookup(lookup(&[("SCRYFALL_MAX_CONCURRENT", "0")]));
+    assert!(matches!(result, Err(ScryfallError::InvalidConfig(_))));
+}
+
+#[tokio::test]
+async fn test_retries_server_errors_up_to_max_retries() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/cards/named"))
+        .respond_with(ResponseTemplate::new(503).insert_header("retry-after", "0"))
+        .up_to_n_times(2)
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/cards/named"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(card_json("1", "Opt")))
+        .mount(&server)
+        .await;
+
+    let client = ScryfallClient::builder()
+        .base_url(server.uri())
+        .max_retries(2)
+        .build()
+        .unwrap();
+
+    let card = client.fetch_card_named("Opt", false).await.unwrap();
+    assert_eq!(card["name"], "Opt");
+    assert_eq!(client.rate_limiter_stats().acquisitions, 3);
+}
+
+#[tokio::test]
+async fn test_retry_after_holds_back_clients_sharing_the_limiter() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/cards/named"))
+        .respond_with(ResponseTemplate::new(429).insert_header("retry-after", "1"))
+        .up_to_n_times(1)
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/cards/named"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(card_json("1", "Opt")))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/cards/autocomplete"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "object": "catalog",
+            "total_values": 0,
+            "data": []
+        })))
+        .mount(&server)
+        .await;
+
+    let limiter = Arc::new(RateLimiter::new(2, 0));
+    let first = ScryfallClient::builder()
+        .base_url(server.uri())
+        .max_retries(1)
+        .build()
+        .unwrap()
+        .with_rate_limiter(limiter.clone());
+    let second = client_for(&server).with_rate_limiter(limiter.clone());
+
+    let start = std::time::Instant::now();
+    let (card, _) = tokio::join!(first.fetch_card_named("Opt", false), async {
+        // Start once the 429 has come back
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        second.autocomplete("op").await.unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(900));
+    });
+    assert_eq!(card.unwrap()["name"], "Opt");
+
+    let stats = limiter.stats();
+    assert_eq!(stats.penalties, 1);
+    assert!(stats.total_penalty >= Duration::from_millis(700));
+    assert_eq!(stats.penalty_remaining, Duration::ZERO);
+}
+
+#[tokio::test]
+async fn test_no_retries_by_default() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/cards/named"))
+        .respond_with(ResponseTemplate::new(429).insert_header("retry-after", "0"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = client_for(&server);
+    let err = client.fetch_card_named("Opt", false).await.unwrap_err();
+
+    assert!(matches!(err, ScryfallError::ApiError { status: 429, .. }));
+}
+
+// ==================== Set Catalog Tests ====================
+
+#[tokio::test]
+async fn test_fetch_sets_follows_next_page() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/sets"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "object": "list",
+            "has_more": true,
+            "next_page": format!("{}/sets/page2", server.uri()),
+            "data": [{ "object": "set", "code": "neo", "name": "Kamigawa: Neon Dynasty" }]
+        })))
+        .mount(&server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/sets/page2"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "object": "list",
+            "has_more": false,
+            "data": [{ "object": "set", "code": "lea", "name": "Limited Edition Alpha" }]
+        })))
+        .mount(&server)
+        .await;
+
+    let client = client_for(&server);
+    let sets = client.fetch_sets().await.unwrap();
+
+    let codes: Vec<_> = sets.iter().map(|s| s["code"].as_str().unwrap()).collect();
+    assert_eq!(codes, ["neo", "lea"]);
+}
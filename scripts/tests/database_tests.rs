@@ -0,0 +1,1366 @@
+//! These tests need a Postgres database: run them with
+//! `DATABASE_URL=postgres://... cargo test --test database_tests -- --ignored`.
+//! Most share the database's tables and only touch rows with `test-` ids. Tests that need a
+//! table to themselves, and every bulk import (which also writes `db_metadata` and runs
+//! `ANALYZE`), run in a schema of their own made by `schema_db`.
+
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use futures::StreamExt;
+use scripts::{
+    BulkImport, BulkImportOptions, ColorMatch, Database, FetchLimits, Progress, ScryfallClient,
+    ScryfallError, StoreSummary,
+};
+use serde_json::json;
+use std::collections::HashSet;
+use std::io::Write;
+use std::sync::Mutex;
+use wiremock::matchers::{header, method, path, query_param};
+use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+/// A plain pool on the same database, for setting up rows the `Database` API cannot write
+async fn raw_pool() -> sqlx::PgPool {
+    sqlx::PgPool::connect(&std::env::var("DATABASE_URL").unwrap())
+        .await
+        .unwrap()
+}
+
+/// DATABASE_URL with `schema` as the search path, for tests that need tables of their own
+fn schema_url(schema: &str) -> String {
+    let url = std::env::var("DATABASE_URL").unwrap();
+    let separator = if url.contains('?') { '&' } else { '?' };
+    format!("{url}{separator}options=-c%20search_path%3D{schema}")
+}
+
+/// A `Database` on an empty schema of its own, replacing one left by an earlier run
+async fn schema_db(schema: &str) -> Database {
+    let pool = raw_pool().await;
+    for statement in [
+        format!("DROP SCHEMA IF EXISTS {} CASCADE", schema),
+        format!("CREATE SCHEMA {}", schema),
+    ] {
+        sqlx::query(&statement).execute(&pool).await.unwrap();
+    }
+    Database::connect(&schema_url(schema)).await.unwrap()
+}
+
+/// Drop a schema made by `schema_db`
+async fn drop_schema(schema: &str) {
+    sqlx::query(&format!("DROP SCHEMA {} CASCADE", schema))
+        .execute(&raw_pool().await)
+        .await
+        .unwrap();
+}
+
+/// Serve a bulk data catalog whose `default_cards` download is `cards`
+async fn mount_bulk(server: &MockServer, cards: &[serde_json::Value]) {
+    mount_bulk_download(server, ResponseTemplate::new(200).set_body_json(cards)).await;
+}
+
+/// Serve a bulk data catalog whose `default_cards` download gets `download` as the response
+async fn mount_bulk_download(server: &MockServer, download: ResponseTemplate) {
+    Mock::given(method("GET"))
+        .and(path("/bulk-data"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "object": "list",
+            "data": [{
+                "type": "default_cards",
+                "updated_at": "2024-06-01T09:00:00.000+00:00",
+                "download_uri": format!("{}/default-cards.json", server.uri())
+            }]
+        })))
+        .mount(server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/default-cards.json"))
+        .respond_with(download)
+        .mount(server)
+        .await;
+}
+
+// ==================== Card Id Tests ====================
+
+#[tokio::test]
+#[ignore = "needs a Postgres DATABASE_URL"]
+async fn test_upsert_card_without_id_errors() {
+    let db = Database::new().await.unwrap();
+
+    let err = db
+        .upsert_card(&json!({ "name": "Nameless Wonder" }))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, sqlx::Error::InvalidArgument(_)));
+    assert!(err.to_string().contains("Nameless Wonder"));
+
+    assert!(
+        db.upsert_card(&json!({ "id": "", "name": "Empty" }))
+            .await
+            .is_err()
+    );
+    assert_eq!(db.get_card_by_id("").await.unwrap(), None);
+}
+
+#[tokio::test]
+#[ignore = "needs a Postgres DATABASE_URL"]
+async fn test_upsert_cards_batch_skips_cards_without_id() {
+    let db = Database::new().await.unwrap();
+
+    let cards = vec![
+        json!({ "id": "test-batch-1", "name": "Kept" }),
+        json!({ "name": "No Id" }),
+        json!({ "id": "", "name": "Empty Id" }),
+        json!({ "id": "test-batch-2", "name": "Also Kept" }),
+    ];
+    assert_eq!(db.upsert_cards_batch(&cards).await.unwrap(), 2);
+
+    let stored = db.get_card_by_id("test-batch-2").await.unwrap().unwrap();
+    assert_eq!(stored["name"], "Also Kept");
+    assert_eq!(db.get_card_by_id("").await.unwrap(), None);
+}
+
+#[tokio::test]
+#[ignore = "needs a Postgres DATABASE_URL"]
+async fn test_upsert_cards_stream_commits_in_batches() {
+    let db = Database::new().await.unwrap();
+    let cards = futures::stream::iter(0..7).map(|i| {
+        if i == 3 {
+            json!({ "name": "No Id" })
+        } else {
+            json!({ "id": format!("test-stream-{}", i), "name": "Stream Probe" })
+        }
+    });
+
+    assert_eq!(db.upsert_cards_stream(cards, 3).await.unwrap(), 6);
+    assert!(db.get_card_by_id("test-stream-6").await.unwrap().is_some());
+}
+
+// ==================== Card Lookup Tests ====================
+
+#[tokio::test]
+#[ignore = "needs a Postgres DATABASE_URL"]
+async fn test_get_card_by_id_missing_row_is_none() {
+    let db = Database::new().await.unwrap();
+    assert_eq!(db.get_card_by_id("test-no-such-card").await.unwrap(), None);
+}
+
+#[tokio::test]
+#[ignore = "needs a Postgres DATABASE_URL"]
+async fn test_get_card_by_id_corrupt_json_errors() {
+    let db = Database::new().await.unwrap();
+    let pool = raw_pool().await;
+    sqlx::query(
+        "INSERT INTO cards (id, name, raw_json) VALUES ('test-corrupt', 'Corrupt', 'null')
+         ON CONFLICT (id) DO UPDATE SET raw_json = 'null'",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let err = db.get_card_by_id("test-corrupt").await.unwrap_err();
+    assert!(matches!(err, sqlx::Error::Decode(_)));
+}
+
+// ==================== Paging Tests ====================
+
+#[tokio::test]
+#[ignore = "needs a Postgres DATABASE_URL"]
+async fn test_search_by_name_paged_covers_count() {
+    let db = Database::new().await.unwrap();
+    let cards: Vec<_> = (0..5)
+        .map(|i| {
+            json!({
+                "id": format!("test-page-{}", i),
+                "name": format!("Pagination Probe {}", i)
+            })
+        })
+        .collect();
+    db.upsert_cards_batch(&cards).await.unwrap();
+
+    assert_eq!(db.count_by_name("pagination probe").await.unwrap(), 5);
+
+    let first = db
+        .search_by_name_paged("pagination probe", 2, 0)
+        .await
+        .unwrap();
+    let last = db
+        .search_by_name_paged("pagination probe", 2, 4)
+        .await
+        .unwrap();
+    assert_eq!(first.len(), 2);
+    assert_eq!(first[0]["name"], "Pagination Probe 0");
+    assert_eq!(last.len(), 1);
+    assert_eq!(last[0]["name"], "Pagination Probe 4");
+}
+
+// ==================== Keyword Tests ====================
+
+#[tokio::test]
+#[ignore = "needs a Postgres DATABASE_URL"]
+async fn test_search_by_keyword_matches_whole_keywords() {
+    let db = Database::new().await.unwrap();
+    let cards = vec![
+        json!({ "id": "test-kw-1", "name": "Test Flyer", "keywords": ["Flying", "First strike"] }),
+        json!({ "id": "test-kw-2", "name": "Test Striker", "keywords": ["Double strike"] }),
+    ];
+    db.upsert_cards_batch(&cards).await.unwrap();
+
+    let ids = |cards: Vec<serde_json::Value>| -> Vec<String> {
+        cards
+            .iter()
+            .filter_map(|card| card["id"].as_str())
+            .filter(|id| id.starts_with("test-kw-"))
+            .map(String::from)
+            .collect()
+    };
+    assert_eq!(
+        ids(db.search_by_keyword("first strike").await.unwrap()),
+        ["test-kw-1"]
+    );
+    assert_eq!(
+        ids(db.search_by_keyword("Double Strike").await.unwrap()),
+        ["test-kw-2"]
+    );
+    assert!(ids(db.search_by_keyword("strike").await.unwrap()).is_empty());
+}
+
+// ==================== Fetch and Store Tests ====================
+
+#[tokio::test]
+#[ignore = "needs a Postgres DATABASE_URL"]
+async fn test_fetch_and_store_reports_summary() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/cards/search"))
+        .and(query_param("page", "2"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "object": "list",
+            "total_cards": 3,
+            "has_more": false,
+            "data": [{ "id": "test-store-3", "name": "Store Probe 3" }]
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/cards/search"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "object": "list",
+            "total_cards": 3,
+            "has_more": true,
+            "next_page": format!("{}/cards/search?q=probe&page=2", server.uri()),
+            "data": [
+                { "id": "test-store-1", "name": "Store Probe 1" },
+                { "id": "test-store-2", "name": "Store Probe 2" }
+            ]
+        })))
+        .mount(&server)
+        .await;
+
+    let db = Database::new().await.unwrap();
+    let client = ScryfallClient::new().with_base_url(server.uri());
+
+    let outcome = client
+        .fetch_and_store("probe", &db, FetchLimits::none())
+        .await
+        .unwrap();
+    assert_eq!(
+        outcome.data,
+        StoreSummary {
+            stored: 3,
+            total_cards: 3,
+            pages: 2
+        }
+    );
+    assert!(!outcome.truncated);
+
+    let outcome = client
+        .fetch_and_store("probe", &db, FetchLimits::max_pages(1))
+        .await
+        .unwrap();
+    assert_eq!(outcome.data.stored, 2);
+    assert_eq!(outcome.data.total_cards, 3);
+    assert!(outcome.truncated);
+}
+
+// ==================== Distinct Value Tests ====================
+
+#[tokio::test]
+#[ignore = "needs a Postgres DATABASE_URL"]
+async fn test_distinct_values_for_filter_menus() {
+    let db = Database::new().await.unwrap();
+    let cards = vec![
+        json!({
+            "id": "test-distinct-1",
+            "name": "Distinct Probe",
+            "set": "tdst",
+            "rarity": "mythic",
+            "keywords": ["Probing", "Flying"]
+        }),
+        json!({ "id": "test-distinct-2", "name": "Distinct Probe", "set": "tdst" }),
+    ];
+    db.upsert_cards_batch(&cards).await.unwrap();
+
+    let sets = db.distinct_sets().await.unwrap();
+    assert_eq!(sets.iter().filter(|set| *set == "tdst").count(), 1);
+    assert!(sets.is_sorted());
+    assert!(
+        db.distinct_rarities()
+            .await
+            .unwrap()
+            .contains(&"mythic".to_string())
+    );
+    let keywords = db.distinct_keywords().await.unwrap();
+    assert!(keywords.contains(&"Probing".to_string()));
+    assert!(!keywords.contains(&String::new()));
+}
+
+#[tokio::test]
+#[ignore = "needs a Postgres DATABASE_URL"]
+async fn test_fetch_card_by_id_caches_in_database() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/cards/test-cached-1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "object": "card",
+            "id": "test-cached-1",
+            "name": "Cache Probe"
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let db = Database::new().await.unwrap();
+    sqlx::query("DELETE FROM cards WHERE id = 'test-cached-1'")
+        .execute(&raw_pool().await)
+        .await
+        .unwrap();
+    let client = ScryfallClient::new().with_base_url(server.uri());
+
+    let fetched = client.fetch_card_by_id("test-cached-1", &db).await.unwrap();
+    let cached = client.fetch_card_by_id("test-cached-1", &db).await.unwrap();
+    assert_eq!(fetched["name"], "Cache Probe");
+    assert_eq!(cached, fetched);
+}
+
+// ==================== Maintenance Tests ====================
+
+#[tokio::test]
+#[ignore = "needs a Postgres DATABASE_URL"]
+async fn test_analyze_refreshes_statistics() {
+    let db = schema_db("test_analyze").await;
+    db.analyze().await.unwrap();
+
+    let (analyzed,): (bool,) = sqlx::query_as(
+        "SELECT last_analyze IS NOT NULL FROM pg_stat_user_tables
+         WHERE schemaname = 'test_analyze' AND relname = 'cards'",
+    )
+    .fetch_one(&raw_pool().await)
+    .await
+    .unwrap();
+    assert!(analyzed);
+
+    drop_schema("test_analyze").await;
+}
+
+// ==================== Color Identity Tests ====================
+
+#[tokio::test]
+#[ignore = "needs a Postgres DATABASE_URL"]
+async fn test_search_in_color_identity() {
+    let db = Database::new().await.unwrap();
+    let card = |id: &str, name: &str, identity: &[&str], commander: &str| {
+        json!({
+            "id": id,
+            "name": name,
+            "color_identity": identity,
+            "legalities": { "commander": commander }
+        })
+    };
+    let cards = vec![
+        card("test-ci-1", "Identity Probe Red", &["R"], "legal"),
+        card("test-ci-2", "Identity Probe Gruul", &["R", "G"], "legal"),
+        card("test-ci-3", "Identity Probe Blue", &["U"], "legal"),
+        card("test-ci-4", "Identity Probe Banned", &["R"], "banned"),
+        card("test-ci-5", "Identity Probe Rock", &[], "legal"),
+    ];
+    db.upsert_cards_batch(&cards).await.unwrap();
+
+    let probes = |cards: Vec<serde_json::Value>| -> Vec<String> {
+        cards
+            .iter()
+            .filter_map(|card| card["id"].as_str())
+            .filter(|id| id.starts_with("test-ci-"))
+            .map(String::from)
+            .collect()
+    };
+    let gruul = ["r".to_string(), "G".to_string()];
+    let found = probes(
+        db.search_in_color_identity(&gruul, 100_000, 0)
+            .await
+            .unwrap(),
+    );
+    assert_eq!(found, ["test-ci-2", "test-ci-1", "test-ci-5"]);
+
+    let colorless = probes(db.search_in_color_identity(&[], 100_000, 0).await.unwrap());
+    assert_eq!(colorless, ["test-ci-5"]);
+}
+
+// ==================== Prune Tests ====================
+
+#[tokio::test]
+#[ignore = "needs a Postgres DATABASE_URL"]
+async fn test_delete_cards_not_in_removes_only_missing_ids() {
+    // A table of its own, so rows other tests insert meanwhile are not pruned
+    let db = schema_db("test_prune").await;
+    let cards = vec![
+        json!({ "id": "test-prune-kept", "name": "Prune Probe" }),
+        json!({ "id": "test-prune-other", "name": "Prune Probe" }),
+        json!({ "id": "test-prune-stale", "name": "Prune Probe" }),
+    ];
+    db.upsert_cards_batch(&cards).await.unwrap();
+
+    let keep: HashSet<String> = ["test-prune-kept", "test-prune-other", "test-prune-new"]
+        .into_iter()
+        .map(String::from)
+        .collect();
+    assert_eq!(db.delete_cards_not_in(&keep).await.unwrap(), 1);
+    assert!(
+        db.get_card_by_id("test-prune-stale")
+            .await
+            .unwrap()
+            .is_none()
+    );
+    assert_eq!(db.get_card_count().await.unwrap(), 2);
+
+    drop_schema("test_prune").await;
+}
+
+#[tokio::test]
+#[ignore = "needs a Postgres DATABASE_URL"]
+async fn test_prune_skips_empty_and_truncated_snapshots() {
+    let db = schema_db("test_prune_guard").await;
+    db.upsert_card(&json!({ "id": "test-prune-guard", "name": "Prune Guard" }))
+        .await
+        .unwrap();
+    let before = db.get_card_count().await.unwrap();
+
+    assert_eq!(db.delete_cards_not_in(&HashSet::new()).await.unwrap(), 0);
+    assert_eq!(db.get_card_count().await.unwrap(), before);
+
+    let server = MockServer::start().await;
+    mount_bulk(
+        &server,
+        &[json!({ "id": "test-prune-snapshot", "name": "Prune Snapshot" })],
+    )
+    .await;
+    let client = ScryfallClient::new().with_base_url(server.uri());
+    let options = BulkImportOptions {
+        prune_missing: true,
+        ..BulkImportOptions::default()
+    };
+    let events = Mutex::new(Vec::new());
+
+    let imported = client
+        .download_and_store_bulk_with(&db, &options, |event| events.lock().unwrap().push(event))
+        .await
+        .unwrap();
+    assert_eq!(imported, BulkImport::Stored(1));
+    assert!(
+        events
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|event| matches!(event, Progress::PruneSkipped { snapshot: 1, .. }))
+    );
+    assert!(
+        db.get_card_by_id("test-prune-guard")
+            .await
+            .unwrap()
+            .is_some()
+    );
+
+    drop_schema("test_prune_guard").await;
+}
+
+// ==================== Bulk Progress Tests ====================
+
+#[tokio::test]
+#[ignore = "needs a Postgres DATABASE_URL"]
+async fn test_bulk_import_reports_progress_in_order() {
+    let db = schema_db("test_bulk_progress").await;
+    db.upsert_card(&json!({ "id": "test-progress-seed", "name": "Progress Seed" }))
+        .await
+        .unwrap();
+
+    let server = MockServer::start().await;
+    mount_bulk(
+        &server,
+        &[
+            json!({ "id": "test-progress-1", "name": "Progress One" }),
+            json!({ "id": "test-progress-2", "name": "Progress Two" }),
+        ],
+    )
+    .await;
+    let client = ScryfallClient::new().with_base_url(server.uri());
+    let options = BulkImportOptions {
+        batch_size: 1,
+        ..BulkImportOptions::default()
+    };
+    let events = Mutex::new(Vec::new());
+
+    client
+        .download_and_store_bulk_with(&db, &options, |event| events.lock().unwrap().push(event))
+        .await
+        .unwrap();
+
+    // Variant names only, with repeated progress ticks collapsed; a body this small
+    // downloads before the first `Downloading` tick is due
+    let mut names: Vec<String> = events
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|event| {
+            let debug = format!("{:?}", event);
+            debug.split([' ', '(', '{']).next().unwrap().to_string()
+        })
+        .collect();
+    names.dedup();
+    assert_eq!(
+        names,
+        [
+            "FetchingCatalog",
+            "BulkDataFound",
+            "DownloadStarted",
+            "DownloadComplete",
+            "Parsing",
+            "Parsed",
+            "Storing",
+            "StoreComplete",
+            "Analyzing",
+        ]
+    );
+    assert!(events.lock().unwrap().iter().any(|event| matches!(
+        event,
+        Progress::Storing {
+            stored: 2,
+            total: 2,
+            ..
+        }
+    )));
+
+    drop_schema("test_bulk_progress").await;
+}
+
+// ==================== Bulk Cache Tests ====================
+
+#[tokio::test]
+#[ignore = "needs a Postgres DATABASE_URL"]
+async fn test_bulk_catalog_etag_persists_across_clients() {
+    let db = schema_db("test_cache_etag").await;
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/bulk-data"))
+        .and(header("if-none-match", "\"catalog-v1\""))
+        .respond_with(ResponseTemplate::new(304))
+        .with_priority(1)
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/bulk-data"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("etag", "\"catalog-v1\"")
+                .set_body_json(json!({
+                    "object": "list",
+                    "data": [{
+                        "type": "default_cards",
+                        "updated_at": "2024-06-01T09:00:00.000+00:00",
+                        "download_uri": format!("{}/default-cards.json", server.uri())
+                    }]
+                })),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/default-cards.json"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(json!([{ "id": "test-cache-1", "name": "Cache Probe" }])),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let first = ScryfallClient::new().with_base_url(server.uri());
+    assert_eq!(
+        first
+            .download_and_store_bulk_with(&db, &BulkImportOptions::default(), |_| {})
+            .await
+            .unwrap(),
+        BulkImport::Stored(1)
+    );
+
+    // A fresh client, as in the next importer run, starts from the validators in db_metadata
+    let second = ScryfallClient::new().with_base_url(server.uri());
+    assert_eq!(
+        second
+            .download_and_store_bulk_with(&db, &BulkImportOptions::default(), |_| {})
+            .await
+            .unwrap(),
+        BulkImport::UpToDate
+    );
+    assert_eq!(
+        second.bulk_cache_state().catalog.etag.as_deref(),
+        Some("\"catalog-v1\"")
+    );
+
+    drop_schema("test_cache_etag").await;
+}
+
+#[tokio::test]
+#[ignore = "needs a Postgres DATABASE_URL"]
+async fn test_bulk_download_sends_conditional_headers() {
+    let db = schema_db("test_cache_headers").await;
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/default-cards.json"))
+        .and(header("if-none-match", "\"cards-v1\""))
+        // `header` would split the date at its comma, so compare the raw value
+        .and(|request: &Request| {
+            request
+                .headers
+                .get("if-modified-since")
+                .is_some_and(|value| value == "Sat, 01 Jun 2024 09:00:00 GMT")
+        })
+        .respond_with(ResponseTemplate::new(304))
+        .with_priority(1)
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/default-cards.json"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("etag", "\"cards-v1\"")
+                .insert_header("last-modified", "Sat, 01 Jun 2024 09:00:00 GMT")
+                .set_body_json(json!([{ "id": "test-cache-2", "name": "Cache Probe" }])),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+    // The catalog sends no validators, so only the download can be skipped
+    mount_bulk(&server, &[]).await;
+
+    let client = ScryfallClient::new().with_base_url(server.uri());
+    let options = BulkImportOptions::default();
+    assert_eq!(
+        client
+            .download_and_store_bulk_with(&db, &options, |_| {})
+            .await
+            .unwrap(),
+        BulkImport::Stored(1)
+    );
+    assert_eq!(
+        client
+            .download_and_store_bulk_with(&db, &options, |_| {})
+            .await
+            .unwrap(),
+        BulkImport::UpToDate
+    );
+
+    let stored = db
+        .get_metadata("bulk_cache_validators")
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(stored.contains("cards-v1"));
+
+    drop_schema("test_cache_headers").await;
+}
+
+/// Serve a catalog with an ETag that answers a matching `If-None-Match` with 304, and a
+/// one-card download
+async fn mount_etag_bulk(server: &MockServer) {
+    Mock::given(method("GET"))
+        .and(path("/bulk-data"))
+        .and(header("if-none-match", "\"catalog-v1\""))
+        .respond_with(ResponseTemplate::new(304))
+        .with_priority(1)
+        .mount(server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/bulk-data"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("etag", "\"catalog-v1\"")
+                .set_body_json(json!({
+                    "object": "list",
+                    "data": [{
+                        "type": "default_cards",
+                        "updated_at": "2024-06-01T09:00:00.000+00:00",
+                        "download_uri": format!("{}/default-cards.json", server.uri())
+                    }]
+                })),
+        )
+        .mount(server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/default-cards.json"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(json!([{ "id": "test-cache-3", "name": "Cache Probe" }])),
+        )
+        .mount(server)
+        .await;
+}
+
+#[tokio::test]
+#[ignore = "needs a Postgres DATABASE_URL"]
+async fn test_bulk_cache_is_read_from_the_target_database() {
+    let server = MockServer::start().await;
+    mount_etag_bulk(&server).await;
+    let db_a = schema_db("test_cache_a").await;
+    let db_b = schema_db("test_cache_b").await;
+    let client = ScryfallClient::new().with_base_url(server.uri());
+    let options = BulkImportOptions::default();
+
+    let imported = client.download_and_store_bulk_with(&db_a, &options, |_| {});
+    assert_eq!(imported.await.unwrap(), BulkImport::Stored(1));
+
+    // The validators from db_a must not make the client skip filling db_b
+    let imported = client.download_and_store_bulk_with(&db_b, &options, |_| {});
+    assert_eq!(imported.await.unwrap(), BulkImport::Stored(1));
+    assert_eq!(db_b.get_card_count().await.unwrap(), 1);
+
+    let imported = client.download_and_store_bulk_with(&db_a, &options, |_| {});
+    assert_eq!(imported.await.unwrap(), BulkImport::UpToDate);
+
+    drop_schema("test_cache_a").await;
+    drop_schema("test_cache_b").await;
+}
+
+#[tokio::test]
+#[ignore = "needs a Postgres DATABASE_URL"]
+async fn test_bulk_cache_is_skipped_for_an_empty_cards_table() {
+    let server = MockServer::start().await;
+    mount_etag_bulk(&server).await;
+    let db = schema_db("test_cache_wiped").await;
+    let client = ScryfallClient::new().with_base_url(server.uri());
+    let options = BulkImportOptions::default();
+
+    let imported = client.download_and_store_bulk_with(&db, &options, |_| {});
+    assert_eq!(imported.await.unwrap(), BulkImport::Stored(1));
+
+    // Emptying cards keeps db_metadata, validators included
+    sqlx::query("DELETE FROM test_cache_wiped.cards")
+        .execute(&raw_pool().await)
+        .await
+        .unwrap();
+    let imported = client.download_and_store_bulk_with(&db, &options, |_| {});
+    assert_eq!(imported.await.unwrap(), BulkImport::Stored(1));
+    assert_eq!(db.get_card_count().await.unwrap(), 1);
+
+    drop_schema("test_cache_wiped").await;
+}
+
+// ==================== Price Tests ====================
+
+#[tokio::test]
+#[ignore = "needs a Postgres DATABASE_URL"]
+async fn test_search_by_price_uses_typed_columns() {
+    let db = Database::new().await.unwrap();
+    let cards = vec![
+        json!({ "id": "test-price-1", "name": "Price Probe Cheap", "prices": { "usd": "0.01" } }),
+        json!({
+            "id": "test-price-2",
+            "name": "Price Probe Foil",
+            "prices": { "usd": "25.00", "usd_foil": "0.02" }
+        }),
+        json!({ "id": "test-price-3", "name": "Price Probe Unpriced", "prices": { "usd": null } }),
+    ];
+    db.upsert_cards_batch(&cards).await.unwrap();
+
+    // A row written before the price columns existed only has its prices in raw_json
+    sqlx::query(
+        "INSERT INTO cards (id, name, raw_json) VALUES ('test-price-legacy', 'Price Probe Legacy', $1)
+         ON CONFLICT (id) DO UPDATE SET raw_json = EXCLUDED.raw_json, price_usd = NULL",
+    )
+    .bind(json!({
+        "id": "test-price-legacy",
+        "name": "Price Probe Legacy",
+        "prices": { "usd": "0.03" }
+    }))
+    .execute(&raw_pool().await)
+    .await
+    .unwrap();
+    let db = Database::new().await.unwrap();
+
+    let probes = |cards: Vec<serde_json::Value>| -> Vec<String> {
+        cards
+            .iter()
+            .filter_map(|card| card["id"].as_str())
+            .filter(|id| id.starts_with("test-price-"))
+            .map(String::from)
+            .collect()
+    };
+    assert_eq!(
+        probes(db.search_by_price("usd", 0.05).await.unwrap()),
+        ["test-price-1", "test-price-legacy"]
+    );
+    assert_eq!(
+        probes(db.search_by_price("usd_foil", 0.05).await.unwrap()),
+        ["test-price-2"]
+    );
+
+    let err = db.search_by_price("msrp", 1.0).await.unwrap_err();
+    assert!(matches!(err, sqlx::Error::ColumnNotFound(column) if column == "price_msrp"));
+}
+
+// ==================== Copy Tests ====================
+
+#[tokio::test]
+#[ignore = "needs a Postgres DATABASE_URL"]
+async fn test_copy_cards_matches_upsert() {
+    let db = Database::new().await.unwrap();
+    let pool = raw_pool().await;
+    let cards = vec![
+        json!({
+            "id": "test-copy-1",
+            "name": "Copy Probe, \"Quoted\"",
+            "oracle_text": "First line\nSecond line, with a comma",
+            "flavor_text": "C:\\path\\to \\N and \\\"",
+            "keywords": ["Flying", "First strike"],
+            "cmc": 2.0,
+            "reserved": false,
+            "edhrec_rank": 7,
+            "prices": { "usd": "1.50", "usd_foil": null, "eur": null, "tix": null }
+        }),
+        json!({
+            "id": "test-copy-2",
+            "name": "Copy Probe // Back",
+            "card_faces": [{ "mana_cost": "{G}", "oracle_text": "Front face\r\n" }],
+            "prices": { "usd": null }
+        }),
+    ];
+    let rows = || async {
+        let rows: Vec<(serde_json::Value,)> = sqlx::query_as(
+            "SELECT to_jsonb(c) - 'created_at' - 'updated_at' FROM cards c
+             WHERE id LIKE 'test-copy-%' ORDER BY id",
+        )
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        rows
+    };
+    let clear = || async {
+        sqlx::query("DELETE FROM cards WHERE id LIKE 'test-copy-%'")
+            .execute(&pool)
+            .await
+            .unwrap();
+    };
+
+    clear().await;
+    assert_eq!(db.copy_cards(&cards).await.unwrap(), 2);
+    let copied = rows().await;
+
+    clear().await;
+    db.upsert_cards_batch(&cards).await.unwrap();
+    assert_eq!(copied, rows().await);
+    assert_eq!(copied[0].0["name"], cards[0]["name"]);
+    assert_eq!(copied[0].0["oracle_text"], cards[0]["oracle_text"]);
+    assert_eq!(copied[0].0["flavor_text"], cards[0]["flavor_text"]);
+    assert_eq!(copied[0].0["price_usd"], 1.5);
+    assert!(copied[0].0["price_usd_foil"].is_null());
+
+    // Cards that are already stored go through the upsert path instead
+    assert_eq!(db.copy_cards(&cards).await.unwrap(), 2);
+}
+
+// ==================== Bulk Version Tests ====================
+
+#[tokio::test]
+#[ignore = "needs a Postgres DATABASE_URL"]
+async fn test_record_bulk_import_round_trips() {
+    let db = schema_db("test_bulk_version").await;
+
+    db.record_bulk_import("2024-06-01T09:00:00.000+00:00", 5)
+        .await
+        .unwrap();
+    let first_download = db.get_metadata("bulk_downloaded_at").await.unwrap();
+    assert!(first_download.is_some());
+
+    db.record_bulk_import("2024-06-02T09:00:00.000+00:00", 6)
+        .await
+        .unwrap();
+    assert_eq!(
+        db.get_bulk_version().await.unwrap().as_deref(),
+        Some("2024-06-02T09:00:00.000+00:00")
+    );
+    assert_eq!(
+        db.get_metadata("bulk_card_count").await.unwrap().as_deref(),
+        Some("6")
+    );
+    assert!(db.get_metadata("bulk_downloaded_at").await.unwrap() >= first_download);
+
+    drop_schema("test_bulk_version").await;
+}
+
+// ==================== JSONB Migration Tests ====================
+
+#[tokio::test]
+#[ignore = "needs a Postgres DATABASE_URL"]
+async fn test_initialize_migrates_text_raw_json() {
+    // Build an old-style table, with TEXT raw_json and none of the later columns, in a
+    // schema of its own so the shared cards table is left alone
+    Database::new().await.unwrap();
+    let pool = raw_pool().await;
+    for statement in [
+        "DROP SCHEMA IF EXISTS test_jsonb_migration CASCADE",
+        "CREATE SCHEMA test_jsonb_migration",
+        "CREATE TABLE test_jsonb_migration.cards (LIKE cards INCLUDING DEFAULTS)",
+        "ALTER TABLE test_jsonb_migration.cards DROP COLUMN search_vector, DROP COLUMN loyalty,
+            DROP COLUMN defense, DROP COLUMN price_usd, DROP COLUMN price_usd_foil,
+            DROP COLUMN price_eur, DROP COLUMN price_tix,
+            ALTER COLUMN raw_json TYPE TEXT",
+        r#"INSERT INTO test_jsonb_migration.cards (id, name, raw_json) VALUES
+            ('test-jsonb-legal', 'Legal', '{"id": "test-jsonb-legal", "legalities": {"commander": "legal"}}'),
+            ('test-jsonb-banned', 'Banned', '{"id": "test-jsonb-banned", "legalities": {"commander": "banned"}}')"#,
+    ] {
+        sqlx::query(statement).execute(&pool).await.unwrap();
+    }
+
+    let db = Database::connect(&schema_url("test_jsonb_migration"))
+        .await
+        .unwrap();
+
+    let (data_type,): (String,) = sqlx::query_as(
+        "SELECT data_type FROM information_schema.columns
+         WHERE table_schema = 'test_jsonb_migration' AND table_name = 'cards'
+           AND column_name = 'raw_json'",
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(data_type, "jsonb");
+
+    let legal = db
+        .search_json("legalities.commander", "legal")
+        .await
+        .unwrap();
+    assert_eq!(legal.len(), 1);
+    assert_eq!(legal[0]["id"], "test-jsonb-legal");
+
+    sqlx::query("DROP SCHEMA test_jsonb_migration CASCADE")
+        .execute(&pool)
+        .await
+        .unwrap();
+}
+
+// ==================== Bulk Payload Tests ====================
+
+fn gzip(bytes: &[u8]) -> Vec<u8> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(bytes).unwrap();
+    encoder.finish().unwrap()
+}
+
+/// Runs a bulk import against `download` and returns its result
+async fn import_bulk_download(
+    db: &Database,
+    download: ResponseTemplate,
+) -> Result<BulkImport, ScryfallError> {
+    let server = MockServer::start().await;
+    mount_bulk_download(&server, download).await;
+    ScryfallClient::new()
+        .with_base_url(server.uri())
+        .download_and_store_bulk_with(db, &BulkImportOptions::default(), |_| {})
+        .await
+}
+
+#[tokio::test]
+#[ignore = "needs a Postgres DATABASE_URL"]
+async fn test_bulk_import_decodes_gzip_body() {
+    let db = schema_db("test_payload_gzip").await;
+    let body = json!([{ "id": "test-payload-gzip", "name": "Gzip Card" }]).to_string();
+
+    let imported = import_bulk_download(
+        &db,
+        ResponseTemplate::new(200)
+            .set_body_raw(gzip(body.as_bytes()), "application/json")
+            .insert_header("content-encoding", "gzip"),
+    )
+    .await
+    .unwrap();
+    assert_eq!(imported, BulkImport::Stored(1));
+    assert!(
+        db.get_card_by_id("test-payload-gzip")
+            .await
+            .unwrap()
+            .is_some()
+    );
+
+    drop_schema("test_payload_gzip").await;
+}
+
+#[tokio::test]
+#[ignore = "needs a Postgres DATABASE_URL"]
+async fn test_bulk_import_rejects_bad_payloads() {
+    let db = schema_db("test_payload_bad").await;
+    let card = json!([{ "id": "test-payload-bad", "name": "Bad Payload" }]).to_string();
+
+    let cases = [
+        // Compressed, but without the Content-Encoding that would let reqwest decode it
+        (
+            ResponseTemplate::new(200).set_body_raw(gzip(card.as_bytes()), "application/json"),
+            "gzip",
+        ),
+        // A CDN or captive portal error page served with 200
+        (
+            ResponseTemplate::new(200)
+                .set_body_raw("<html><body>Maintenance</body></html>", "text/html"),
+            "Content-Type",
+        ),
+        (
+            ResponseTemplate::new(200).set_body_raw(r#"{"object": "error"}"#, "application/json"),
+            "JSON array",
+        ),
+        (
+            ResponseTemplate::new(200).set_body_raw(&card[..card.len() / 2], "application/json"),
+            "Failed to parse",
+        ),
+    ];
+
+    for (download, message) in cases {
+        let err = import_bulk_download(&db, download).await.unwrap_err();
+        assert!(err.to_string().contains(message), "{}", err);
+    }
+    assert!(
+        db.get_card_by_id("test-payload-bad")
+            .await
+            .unwrap()
+            .is_none()
+    );
+
+    drop_schema("test_payload_bad").await;
+}
+
+// ==================== Concurrent Import Tests ====================
+
+#[tokio::test]
+#[ignore = "needs a Postgres DATABASE_URL"]
+async fn test_concurrent_bulk_import_stores_every_batch() {
+    let db = schema_db("test_concurrent").await;
+    // A non-empty table, so the import takes the batched upsert path rather than COPY
+    db.upsert_card(&json!({ "id": "test-conc-seed", "name": "Concurrent Seed" }))
+        .await
+        .unwrap();
+
+    let cards: Vec<_> = (0..50)
+        .map(
+            |i| json!({ "id": format!("test-conc-{:02}", i), "name": format!("Concurrent {}", i) }),
+        )
+        .collect();
+    let server = MockServer::start().await;
+    mount_bulk(&server, &cards).await;
+    let options = BulkImportOptions {
+        batch_size: 7,
+        concurrency: 4,
+        ..BulkImportOptions::default()
+    };
+    let events = Mutex::new(Vec::new());
+
+    let imported = ScryfallClient::new()
+        .with_base_url(server.uri())
+        .download_and_store_bulk_with(&db, &options, |event| events.lock().unwrap().push(event))
+        .await
+        .unwrap();
+    assert_eq!(imported, BulkImport::Stored(50));
+
+    let stored: Vec<usize> = events
+        .lock()
+        .unwrap()
+        .iter()
+        .filter_map(|event| match event {
+            Progress::Storing { stored, .. } => Some(*stored),
+            Progress::StoreComplete { stored, .. } => Some(*stored),
+            _ => None,
+        })
+        .collect();
+    // One tick per batch of 7, then the completion
+    assert_eq!(stored.len(), 8 + 1);
+    assert!(stored.is_sorted());
+    assert_eq!(stored.last(), Some(&50));
+
+    // The 50 imported cards plus the seed
+    assert_eq!(db.get_card_count().await.unwrap(), 51);
+
+    drop_schema("test_concurrent").await;
+}
+
+// ==================== Full-Text Search Tests ====================
+
+#[tokio::test]
+#[ignore = "needs a Postgres DATABASE_URL"]
+async fn test_search_text_matches_oracle_text() {
+    let db = Database::new().await.unwrap();
+    db.upsert_cards_batch(&[
+        json!({
+            "id": "test-text-maker",
+            "name": "Quibbleworth Maker",
+            "oracle_text": "Create two 1/1 Quibbleworth creature tokens."
+        }),
+        json!({
+            "id": "test-text-named",
+            "name": "Quibbleworth Herald",
+            "oracle_text": "Flying"
+        }),
+    ])
+    .await
+    .unwrap();
+    let ids = |cards: Vec<serde_json::Value>| -> Vec<String> {
+        cards
+            .iter()
+            .filter_map(|card| card["id"].as_str())
+            .filter(|id| id.starts_with("test-text-"))
+            .map(|id| id.to_string())
+            .collect()
+    };
+
+    // Stemmed, so "creates" and "token" match "Create" and "tokens"
+    assert_eq!(
+        ids(db.search_text("creates quibbleworth token").await.unwrap()),
+        ["test-text-maker"]
+    );
+    // Names are searched too, and a name match alone is enough
+    assert_eq!(ids(db.search_text("quibbleworth").await.unwrap()).len(), 2);
+
+    // The search vector follows oracle text updates
+    db.upsert_card(&json!({
+        "id": "test-text-maker",
+        "name": "Quibbleworth Maker",
+        "oracle_text": "Draw a card."
+    }))
+    .await
+    .unwrap();
+    assert!(ids(db.search_text("quibbleworth token").await.unwrap()).is_empty());
+}
+
+// ==================== Rulings Tests ====================
+
+#[tokio::test]
+#[ignore = "needs a Postgres DATABASE_URL"]
+async fn test_upsert_rulings_replaces_and_orders() {
+    let db = Database::new().await.unwrap();
+    let rulings = [
+        json!({
+            "object": "ruling",
+            "oracle_id": "test-oracle",
+            "source": "wotc",
+            "published_at": "2021-06-18",
+            "comment": "The newer ruling."
+        }),
+        json!({
+            "object": "ruling",
+            "oracle_id": "test-oracle",
+            "source": "scryfall",
+            "published_at": "2019-01-25",
+            "comment": "The older ruling."
+        }),
+    ];
+
+    assert_eq!(
+        db.upsert_rulings("test-rulings-card", &rulings)
+            .await
+            .unwrap(),
+        2
+    );
+    // Storing again replaces rather than duplicates
+    assert_eq!(
+        db.upsert_rulings("test-rulings-card", &rulings)
+            .await
+            .unwrap(),
+        2
+    );
+
+    let stored = db.get_rulings("test-rulings-card").await.unwrap();
+    assert_eq!(stored, [rulings[1].clone(), rulings[0].clone()]);
+
+    db.upsert_rulings("test-rulings-card", &rulings[..1])
+        .await
+        .unwrap();
+    assert_eq!(
+        db.get_rulings("test-rulings-card").await.unwrap(),
+        &rulings[..1]
+    );
+    assert!(
+        db.get_rulings("test-rulings-missing")
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
+
+// ==================== Set Tests ====================
+
+#[tokio::test]
+#[ignore = "needs a Postgres DATABASE_URL"]
+async fn test_upsert_sets_is_idempotent() {
+    let db = Database::new().await.unwrap();
+    let pool = raw_pool().await;
+    let mut sets = vec![
+        json!({ "code": "test-sets-neo", "name": "Kamigawa", "card_count": 302, "digital": false }),
+        json!({ "code": "test-sets-neo-tokens", "name": "Kamigawa Tokens", "parent_set_code": "test-sets-neo" }),
+        json!({ "name": "Set Without Code" }),
+    ];
+
+    assert_eq!(db.upsert_sets(&sets).await.unwrap(), 2);
+    sets[0]["name"] = json!("Kamigawa: Neon Dynasty");
+    assert_eq!(db.upsert_sets(&sets).await.unwrap(), 2);
+
+    let rows: Vec<(String, String, Option<i32>, Option<String>)> = sqlx::query_as(
+        "SELECT code, name, card_count, parent_set_code FROM sets
+         WHERE code LIKE 'test-sets-%' ORDER BY code",
+    )
+    .fetch_all(&pool)
+    .await
+    .unwrap();
+    assert_eq!(
+        rows,
+        [
+            (
+                "test-sets-neo".to_string(),
+                "Kamigawa: Neon Dynasty".to_string(),
+                Some(302),
+                None
+            ),
+            (
+                "test-sets-neo-tokens".to_string(),
+                "Kamigawa Tokens".to_string(),
+                None,
+                Some("test-sets-neo".to_string())
+            ),
+        ]
+    );
+}
+
+// ==================== Set and Rarity Search Tests ====================
+
+#[tokio::test]
+#[ignore = "needs a Postgres DATABASE_URL"]
+async fn test_search_by_set_and_rarity() {
+    let db = Database::new().await.unwrap();
+    db.upsert_cards_batch(&[
+        json!({ "id": "test-setr-b", "name": "Set Mythic B", "set": "tsr1", "rarity": "mythic" }),
+        json!({ "id": "test-setr-a", "name": "Set Mythic A", "set": "tsr1", "rarity": "mythic" }),
+        json!({ "id": "test-setr-c", "name": "Set Common", "set": "tsr1", "rarity": "common" }),
+        json!({ "id": "test-setr-d", "name": "Other Mythic", "set": "tsr2", "rarity": "mythic" }),
+    ])
+    .await
+    .unwrap();
+    let ids = |cards: Vec<serde_json::Value>| -> Vec<String> {
+        cards
+            .iter()
+            .filter_map(|card| card["id"].as_str())
+            .filter(|id| id.starts_with("test-setr-"))
+            .map(|id| id.to_string())
+            .collect()
+    };
+
+    // Scryfall stores set codes and rarities lowercase; the arguments are lowercased to match
+    assert_eq!(
+        ids(db.search_by_set("TSR1").await.unwrap()),
+        ["test-setr-c", "test-setr-a", "test-setr-b"]
+    );
+    assert_eq!(
+        ids(db.search_by_rarity("Mythic").await.unwrap()),
+        ["test-setr-d", "test-setr-a", "test-setr-b"]
+    );
+    assert_eq!(
+        ids(db.search_by_set_and_rarity("Tsr1", "MYTHIC").await.unwrap()),
+        ["test-setr-a", "test-setr-b"]
+    );
+    assert!(
+        db.search_by_set_and_rarity("tsr2", "common")
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
+
+// ==================== Random Card Tests ====================
+
+#[tokio::test]
+#[ignore = "needs a Postgres DATABASE_URL"]
+async fn test_random_card() {
+    let db = Database::new().await.unwrap();
+    db.upsert_card(&json!({ "id": "test-random-card", "name": "Random Card" }))
+        .await
+        .unwrap();
+
+    let card = db.random_card().await.unwrap().unwrap();
+    assert!(card["id"].is_string());
+
+    // An empty cards table, in a schema of its own
+    let empty = schema_db("test_random_empty").await;
+    assert_eq!(empty.random_card().await.unwrap(), None);
+
+    drop_schema("test_random_empty").await;
+}
+
+// ==================== Color Match Tests ====================
+
+#[tokio::test]
+#[ignore = "needs a Postgres DATABASE_URL"]
+async fn test_search_by_colors_match_modes() {
+    // A table of its own, so pages hold only these cards
+    let db = schema_db("test_color_match").await;
+    db.upsert_cards_batch(&[
+        json!({ "id": "test-colors-r", "name": "Mono Red", "colors": ["R"] }),
+        json!({ "id": "test-colors-rg", "name": "Red Green", "colors": ["R", "G"] }),
+        json!({ "id": "test-colors-g", "name": "Mono Green", "colors": ["G"] }),
+        json!({ "id": "test-colors-c", "name": "Colorless", "colors": [] }),
+        // A transform card: colors only on its faces
+        json!({
+            "id": "test-colors-dfc",
+            "name": "Red Transform",
+            "card_faces": [{ "colors": ["R"] }, { "colors": ["R", "B"] }]
+        }),
+    ])
+    .await
+    .unwrap();
+    let search = |colors: &[&str], mode| {
+        let colors: Vec<String> = colors.iter().map(|c| c.to_string()).collect();
+        let db = &db;
+        async move {
+            db.search_by_colors(&colors, mode, 10, 0)
+                .await
+                .unwrap()
+                .iter()
+                .map(|card| card["id"].as_str().unwrap().to_string())
+                .collect::<Vec<_>>()
+        }
+    };
+
+    assert_eq!(
+        search(&["r"], ColorMatch::Exact).await,
+        ["test-colors-r", "test-colors-dfc"]
+    );
+    assert_eq!(
+        search(&["R"], ColorMatch::AtLeast).await,
+        ["test-colors-r", "test-colors-rg", "test-colors-dfc"]
+    );
+    assert_eq!(
+        search(&["R"], ColorMatch::AtMost).await,
+        ["test-colors-c", "test-colors-r", "test-colors-dfc"]
+    );
+    assert_eq!(
+        search(&["G", "R"], ColorMatch::Exact).await,
+        ["test-colors-rg"]
+    );
+    assert_eq!(search(&[], ColorMatch::Exact).await, ["test-colors-c"]);
+
+    drop_schema("test_color_match").await;
+}
@@ -0,0 +1,188 @@
+use scripts::{Card, cards_to_csv};
+use serde_json::json;
+
+// ==================== Card Deserialization Tests ====================
+
+#[test]
+fn test_card_deserializes_full_fields() {
+    let card: Card = serde_json::from_value(json!({
+        "id": "e3285e6b",
+        "name": "Lightning Bolt",
+        "mana_cost": "{R}",
+        "cmc": 1.0,
+        "type_line": "Instant",
+        "oracle_text": "Lightning Bolt deals 3 damage to any target.",
+        "colors": ["R"],
+        "color_identity": ["R"],
+        "set": "lea",
+        "set_name": "Limited Edition Alpha",
+        "collector_number": "161",
+        "rarity": "common",
+        "image_uris": { "normal": "https://cards.scryfall.io/normal/bolt.jpg" },
+        "legalities": { "modern": "legal", "standard": "not_legal" },
+        "prices": { "usd": "450.00", "eur": null }
+    }))
+    .unwrap();
+
+    assert_eq!(card.cmc, Some(1.0));
+    assert_eq!(card.colors.as_deref(), Some(&["R".to_string()][..]));
+    assert_eq!(card.set, "lea");
+    assert_eq!(card.collector_number, "161");
+    assert_eq!(
+        card.image_uris.unwrap().normal.as_deref(),
+        Some("https://cards.scryfall.io/normal/bolt.jpg")
+    );
+    assert_eq!(card.legalities["modern"], "legal");
+    assert_eq!(card.prices.usd.as_deref(), Some("450.00"));
+    assert_eq!(card.prices.eur, None);
+}
+
+#[test]
+fn test_card_missing_fields_use_defaults() {
+    let card: Card = serde_json::from_value(json!({
+        "id": "abc",
+        "name": "Delver of Secrets // Insectile Aberration"
+    }))
+    .unwrap();
+
+    assert_eq!(card.cmc, None);
+    assert_eq!(card.colors, None);
+    assert!(card.color_identity.is_empty());
+    assert!(card.set_name.is_empty());
+    assert!(card.image_uris.is_none());
+    assert!(card.legalities.is_empty());
+    assert_eq!(card.prices.usd, None);
+}
+
+// ==================== Card Face Tests ====================
+
+#[test]
+fn test_card_faces_deserialize() {
+    let card: Card = serde_json::from_value(json!({
+        "id": "dfc",
+        "name": "Delver of Secrets // Insectile Aberration",
+        "type_line": "Creature — Human Wizard // Creature — Human Insect",
+        "card_faces": [
+            {
+                "name": "Delver of Secrets",
+                "mana_cost": "{U}",
+                "type_line": "Creature — Human Wizard",
+                "oracle_text": "At the beginning of your upkeep, look at the top card of your library.",
+                "power": "1",
+                "toughness": "1",
+                "image_uris": { "normal": "https://cards.scryfall.io/normal/front.jpg" }
+            },
+            {
+                "name": "Insectile Aberration",
+                "mana_cost": "",
+                "type_line": "Creature — Human Insect",
+                "oracle_text": "Flying",
+                "power": "3",
+                "toughness": "2",
+                "image_uris": { "normal": "https://cards.scryfall.io/normal/back.jpg" }
+            }
+        ]
+    }))
+    .unwrap();
+
+    let faces = card.card_faces.as_deref().unwrap();
+    assert_eq!(faces.len(), 2);
+    assert_eq!(faces[1].name, "Insectile Aberration");
+    assert_eq!(faces[1].power.as_deref(), Some("3"));
+}
+
+#[test]
+fn test_front_falls_back_to_first_face() {
+    let card: Card = serde_json::from_value(json!({
+        "id": "dfc",
+        "name": "Delver of Secrets // Insectile Aberration",
+        "type_line": "Creature — Human Wizard // Creature — Human Insect",
+        "card_faces": [
+            {
+                "name": "Delver of Secrets",
+                "mana_cost": "{U}",
+                "power": "1",
+                "image_uris": { "normal": "https://cards.scryfall.io/normal/front.jpg" }
+            },
+            { "name": "Insectile Aberration", "power": "3" }
+        ]
+    }))
+    .unwrap();
+
+    let front = card.front();
+    assert_eq!(front.name, "Delver of Secrets");
+    assert_eq!(front.mana_cost.as_deref(), Some("{U}"));
+    assert_eq!(front.power.as_deref(), Some("1"));
+    // Top-level fields are kept when present
+    assert_eq!(
+        front.type_line.as_deref(),
+        Some("Creature — Human Wizard // Creature — Human Insect")
+    );
+    assert_eq!(
+        front.image_uris.unwrap().normal.as_deref(),
+        Some("https://cards.scryfall.io/normal/front.jpg")
+    );
+}
+
+#[test]
+fn test_front_of_single_faced_card_uses_top_level() {
+    let card: Card = serde_json::from_value(json!({
+        "id": "bolt",
+        "name": "Lightning Bolt",
+        "mana_cost": "{R}",
+        "oracle_text": "Lightning Bolt deals 3 damage to any target."
+    }))
+    .unwrap();
+
+    let front = card.front();
+    assert_eq!(front.name, "Lightning Bolt");
+    assert_eq!(front.mana_cost.as_deref(), Some("{R}"));
+    assert!(card.card_faces.is_none());
+}
+
+// ==================== CSV Export Tests ====================
+
+#[test]
+fn test_cards_to_csv() {
+    let cards: Vec<Card> = serde_json::from_value(json!([
+        {
+            "id": "1",
+            "name": "Lightning Bolt",
+            "mana_cost": "{R}",
+            "cmc": 1.0,
+            "type_line": "Instant",
+            "set": "lea",
+            "collector_number": "161",
+            "rarity": "common"
+        },
+        {
+            "id": "2",
+            "name": "Delver of Secrets // Insectile Aberration",
+            "cmc": 1.0,
+            "type_line": "Creature — Human Wizard // Creature — Human Insect",
+            "set": "isd",
+            "collector_number": "51",
+            "rarity": "common",
+            "card_faces": [
+                { "name": "Delver of Secrets", "mana_cost": "{U}" },
+                { "name": "Insectile Aberration", "mana_cost": "" }
+            ]
+        },
+        { "id": "3", "name": "Borborygmos, \"The\" Enraged", "set": "tst" }
+    ]))
+    .unwrap();
+
+    let csv = cards_to_csv(&cards);
+    let lines: Vec<_> = csv.lines().collect();
+
+    assert_eq!(
+        lines,
+        [
+            "name,set_code,collector_number,rarity,cmc,mana_cost,type_line",
+            "Lightning Bolt,lea,161,common,1,{R},Instant",
+            "Delver of Secrets // Insectile Aberration,isd,51,common,1,{U},\
+             Creature — Human Wizard // Creature — Human Insect",
+            "\"Borborygmos, \"\"The\"\" Enraged\",tst,,,,,",
+        ]
+    );
+}
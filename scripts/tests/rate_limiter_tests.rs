@@ -0,0 +1,94 @@
+use scripts::RateLimiter;
+use std::time::{Duration, Instant};
+
+// ==================== Stats Tests ====================
+
+#[tokio::test]
+async fn test_stats_start_at_zero() {
+    let limiter = RateLimiter::new(1, 20);
+    let stats = limiter.stats();
+    assert_eq!(stats.acquisitions, 0);
+    assert_eq!(stats.throttled, 0);
+    assert_eq!(stats.total_sleep, Duration::ZERO);
+    assert_eq!(stats.configured_delay, Duration::from_millis(20));
+}
+
+#[tokio::test]
+async fn test_stats_count_acquisitions_and_sleeps() {
+    let limiter = RateLimiter::new(1, 20);
+    for _ in 0..3 {
+        limiter.acquire().await;
+    }
+
+    let stats = limiter.stats();
+    assert_eq!(stats.acquisitions, 3);
+    // The first request goes straight through; the next two wait for the delay
+    assert_eq!(stats.throttled, 2);
+    assert!(stats.total_sleep > Duration::from_millis(20));
+    assert!(stats.total_sleep <= Duration::from_millis(40));
+}
+
+// ==================== Token Bucket Tests ====================
+
+#[tokio::test]
+async fn test_token_bucket_allows_burst_up_to_capacity() {
+    let limiter = RateLimiter::token_bucket(3, 20.0);
+    for _ in 0..3 {
+        limiter.acquire().await;
+    }
+
+    let stats = limiter.stats();
+    assert_eq!(stats.acquisitions, 3);
+    assert_eq!(stats.throttled, 0);
+    assert_eq!(stats.configured_delay, Duration::from_millis(50));
+}
+
+#[tokio::test]
+async fn test_token_bucket_waits_for_refill_when_empty() {
+    let limiter = RateLimiter::token_bucket(2, 20.0);
+    let start = Instant::now();
+    for _ in 0..4 {
+        limiter.acquire().await;
+    }
+
+    // Two tokens up front, then one every 50ms
+    assert!(start.elapsed() >= Duration::from_millis(90));
+    assert_eq!(limiter.stats().throttled, 2);
+}
+
+#[tokio::test]
+async fn test_token_bucket_refills_after_idle() {
+    let limiter = RateLimiter::token_bucket(2, 20.0);
+    limiter.acquire().await;
+    limiter.acquire().await;
+
+    tokio::time::sleep(Duration::from_millis(120)).await;
+    limiter.acquire().await;
+    limiter.acquire().await;
+
+    assert_eq!(limiter.stats().throttled, 0);
+}
+
+// ==================== Penalty Tests ====================
+
+#[tokio::test]
+async fn test_penalize_holds_back_acquire() {
+    let limiter = RateLimiter::new(1, 0);
+    limiter.penalize(Instant::now() + Duration::from_millis(60));
+    // An earlier deadline does not shorten the window
+    limiter.penalize(Instant::now() + Duration::from_millis(10));
+
+    let stats = limiter.stats();
+    assert_eq!(stats.penalties, 2);
+    assert!(stats.penalty_remaining > Duration::from_millis(40));
+
+    let start = Instant::now();
+    limiter.acquire().await;
+    assert!(start.elapsed() >= Duration::from_millis(50));
+
+    let stats = limiter.stats();
+    assert_eq!(stats.penalty_remaining, Duration::ZERO);
+    assert_eq!(stats.throttled, 1);
+    assert!(stats.total_penalty >= Duration::from_millis(50));
+    assert!(stats.total_sleep >= stats.total_penalty);
+}
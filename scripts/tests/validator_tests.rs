@@ -1,4 +1,4 @@
-use scripts::{QueryValidationError, QueryValidator, ScryfallError};
+use scripts::{LintSeverity, QueryValidationError, QueryValidator, ScryfallError};
 
 fn validator() -> QueryValidator {
     QueryValidator::new()
@@ -246,6 +246,19 @@ fn test_encode_query_with_quotes() {
     assert!(encoded.contains("%22"));
 }
 
+#[test]
+fn test_validate_and_encode() {
+    let v = validator();
+    assert_eq!(
+        v.validate_and_encode("t:creature c:red").unwrap(),
+        "t%3Acreature%20c%3Ared"
+    );
+    assert!(matches!(
+        v.validate_and_encode("(t:elf"),
+        Err(QueryValidationError::UnbalancedParentheses)
+    ));
+}
+
 // ==================== Edge Cases ====================
 
 #[test]
@@ -277,6 +290,285 @@ fn test_default_trait() {
     assert!(v.validate("type:creature").is_ok());
 }
 
+// ==================== Mana Cost Tests ====================
+
+#[test]
+fn test_valid_mana_symbols() {
+    let v = validator();
+    assert!(v.validate("m:{2}{U}").is_ok());
+    assert!(v.validate("mana:{X}{R}{R}").is_ok());
+    assert!(v.validate("m:{W/U}{G/P}").is_ok());
+    assert!(v.validate("m:{2/W}{C}{S}").is_ok());
+    assert!(v.validate("m>={G/U/P}").is_ok());
+    assert!(v.validate("m:{10}").is_ok());
+    assert!(v.validate("m:2WW").is_ok());
+    assert!(v.validate("-m:{u}{u} t:wizard").is_ok());
+}
+
+#[test]
+fn test_invalid_mana_symbol() {
+    let v = validator();
+    assert!(matches!(
+        v.validate("m:{ZZ}"),
+        Err(QueryValidationError::InvalidComparison(term)) if term == "m:{ZZ}"
+    ));
+    assert!(matches!(
+        v.validate("mana:{W/W}"),
+        Err(QueryValidationError::InvalidComparison(_))
+    ));
+    assert!(matches!(
+        v.validate("m:{}"),
+        Err(QueryValidationError::InvalidComparison(_))
+    ));
+    assert!(matches!(
+        v.validate("m:{P/G}"),
+        Err(QueryValidationError::InvalidComparison(_))
+    ));
+}
+
+#[test]
+fn test_unbalanced_mana_braces() {
+    let v = validator();
+    assert!(matches!(
+        v.validate("m:{2}{U"),
+        Err(QueryValidationError::InvalidComparison(_))
+    ));
+    assert!(matches!(
+        v.validate("m:2}{U}"),
+        Err(QueryValidationError::InvalidComparison(_))
+    ));
+    assert!(matches!(
+        v.validate("m:{2{U}"),
+        Err(QueryValidationError::InvalidComparison(_))
+    ));
+}
+
+#[test]
+fn test_braces_outside_mana_field_not_checked() {
+    let v = validator();
+    assert!(v.validate("o:\"{T}: Add {ZZ}\"").is_ok());
+}
+
+// ==================== Glued Operator Tests ====================
+
+#[test]
+fn test_operator_glued_to_type_value() {
+    let v = validator();
+    assert!(matches!(
+        v.validate("type:creatureor c:red"),
+        Err(QueryValidationError::GluedOperator(term)) if term == "type:creatureor"
+    ));
+    assert!(matches!(
+        v.validate("t:instantand c:blue"),
+        Err(QueryValidationError::GluedOperator(_))
+    ));
+    assert!(matches!(
+        v.validate("c:redor t:goblin"),
+        Err(QueryValidationError::GluedOperator(_))
+    ));
+}
+
+#[test]
+fn test_operator_glued_to_next_field() {
+    let v = validator();
+    assert!(matches!(
+        v.validate("c:redorc:blue"),
+        Err(QueryValidationError::GluedOperator(_))
+    ));
+    assert!(matches!(
+        v.validate("t:elfandpow>=2"),
+        Err(QueryValidationError::GluedOperator(_))
+    ));
+}
+
+#[test]
+fn test_operator_glued_to_parentheses_is_accepted() {
+    let v = validator();
+    assert!(v.validate("t:elf or(t:goblin)").is_ok());
+    assert!(v.validate("(t:elf)or t:goblin").is_ok());
+    assert!(v.validate("(t:elf)or(t:goblin)").is_ok());
+    assert!(v.validate("t:elf and(c:g)").is_ok());
+}
+
+#[test]
+fn test_words_containing_operators_pass() {
+    let v = validator();
+    assert!(v.validate("name:gruulor").is_ok());
+    assert!(v.validate("Gruulor t:creature").is_ok());
+    assert!(v.validate("o:color").is_ok());
+    assert!(v.validate("t:elf or t:goblin").is_ok());
+    assert!(v.validate("(t:elf) or (t:goblin)").is_ok());
+    assert!(v.validate("o:\"draw or discard\"").is_ok());
+    assert!(v.validate("t:land order:name").is_ok());
+    assert!(v.validate("c:red (o:honor)").is_ok());
+}
+
+// ==================== Ordering Field Tests ====================
+
+#[test]
+fn test_valid_ordering_fields() {
+    let v = validator();
+    assert!(v.validate("t:dragon order:cmc").is_ok());
+    assert!(v.validate("t:dragon order:edhrec dir:desc").is_ok());
+    assert!(v.validate("t:dragon direction:asc").is_ok());
+    assert!(v.validate("c:red unique:prints").is_ok());
+    assert!(v.validate("c:red Unique:Art Order:USD").is_ok());
+}
+
+#[test]
+fn test_invalid_ordering_values() {
+    let v = validator();
+    assert!(matches!(
+        v.validate("t:dragon order:banana"),
+        Err(QueryValidationError::InvalidComparison(term)) if term == "order:banana"
+    ));
+    assert!(matches!(
+        v.validate("t:dragon dir:up"),
+        Err(QueryValidationError::InvalidComparison(_))
+    ));
+    assert!(matches!(
+        v.validate("t:dragon unique:print"),
+        Err(QueryValidationError::InvalidComparison(_))
+    ));
+}
+
+// ==================== Set Code Tests ====================
+
+#[test]
+fn test_default_validator_accepts_any_set_code() {
+    let v = validator();
+    assert!(v.validate("set:zzz").is_ok());
+}
+
+#[test]
+fn test_known_set_codes_accepted() {
+    let v = QueryValidator::with_set_codes(["neo", "lea", "MH2"]);
+    assert!(v.validate("set:neo").is_ok());
+    assert!(v.validate("s:LEA t:creature").is_ok());
+    assert!(v.validate("e:mh2 or edition:neo").is_ok());
+}
+
+#[test]
+fn test_unknown_set_code_suggests_closest() {
+    let v = QueryValidator::with_set_codes(["neo", "lea", "mh2"]);
+    assert!(matches!(
+        v.validate("set:nen"),
+        Err(QueryValidationError::InvalidField(msg)) if msg == "set:nen (did you mean neo?)"
+    ));
+    assert!(matches!(
+        v.validate("t:elf e:xyzzy"),
+        Err(QueryValidationError::InvalidField(msg)) if msg == "e:xyzzy"
+    ));
+}
+
+// ==================== Lint Tests ====================
+
+#[test]
+fn test_lint_clean_query() {
+    let v = validator();
+    assert!(
+        v.lint("o:\"draw a card\" (t:elf or t:goblin) -c:blue")
+            .is_empty()
+    );
+}
+
+#[test]
+fn test_lint_reports_validation_error() {
+    let v = validator();
+    let lints = v.lint("(t:elf");
+    assert_eq!(lints.len(), 1);
+    assert_eq!(lints[0].severity, LintSeverity::Error);
+    assert_eq!(lints[0].span, 0..6);
+    assert_eq!(
+        lints[0].message,
+        QueryValidationError::UnbalancedParentheses.to_string()
+    );
+}
+
+#[test]
+fn test_lint_lone_dash() {
+    let v = validator();
+    let lints = v.lint("t:elf - c:green");
+    assert_eq!(lints.len(), 1);
+    assert_eq!(lints[0].severity, LintSeverity::Warning);
+    assert_eq!(lints[0].span, 6..7);
+}
+
+#[test]
+fn test_lint_unquoted_multi_word_text() {
+    let v = validator();
+    let query = "c:red o:draw a card";
+    let lints = v.lint(query);
+    assert_eq!(lints.len(), 1);
+    assert_eq!(lints[0].severity, LintSeverity::Warning);
+    assert_eq!(&query[lints[0].span.clone()], "o:draw a");
+    assert!(lints[0].message.contains("o:\"draw a\""));
+
+    assert!(v.lint("o:draw or o:discard").is_empty());
+    assert!(v.lint("o:draw c:blue").is_empty());
+}
+
+#[test]
+fn test_lint_redundant_parens() {
+    let v = validator();
+    let query = "((t:elf or t:goblin)) c:green";
+    let lints = v.lint(query);
+    assert_eq!(lints.len(), 1);
+    assert_eq!(&query[lints[0].span.clone()], "((t:elf or t:goblin))");
+
+    assert!(v.lint("((t:elf or t:goblin) c:green)").is_empty());
+}
+
+#[test]
+fn test_lint_operator_glued_to_parentheses() {
+    let v = validator();
+    let query = "t:elf or(t:goblin)";
+    let lints = v.lint(query);
+    assert_eq!(lints.len(), 1);
+    assert_eq!(lints[0].severity, LintSeverity::Warning);
+    assert_eq!(&query[lints[0].span.clone()], "or");
+
+    let query = "(t:elf)AND c:green";
+    assert_eq!(&query[v.lint(query)[0].span.clone()], "AND");
+
+    assert!(v.lint("(t:elf) or (t:goblin)").is_empty());
+    assert!(v.lint("o:\"(t:elf)or\" c:green").is_empty());
+}
+
+#[test]
+fn test_lint_errors_come_before_warnings() {
+    let v = validator();
+    let lints = v.lint("- t:elf or");
+    let severities: Vec<_> = lints.iter().map(|lint| lint.severity).collect();
+    assert_eq!(severities, [LintSeverity::Error, LintSeverity::Warning]);
+}
+
+// ==================== Clause Splitting Tests ====================
+
+#[test]
+fn test_split_clauses_top_level() {
+    let v = validator();
+    assert_eq!(
+        v.split_clauses("(c:red or c:blue) cmc<=3 -is:reprint"),
+        ["(c:red or c:blue)", "cmc<=3", "-is:reprint"]
+    );
+}
+
+#[test]
+fn test_split_clauses_keeps_quotes_and_nested_groups() {
+    let v = validator();
+    assert_eq!(
+        v.split_clauses("  o:\"draw a card\"   -(t:elf or (t:goblin c:red)) or name:\"a (b)\""),
+        [
+            "o:\"draw a card\"",
+            "-(t:elf or (t:goblin c:red))",
+            "or",
+            "name:\"a (b)\""
+        ]
+    );
+    assert!(v.split_clauses("   ").is_empty());
+}
+
 // ==================== Error Display Tests ====================
 
 #[test]